@@ -0,0 +1,15 @@
+use std::io;
+use std::time::Duration;
+use yapp::AsyncPasswordReader;
+
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    let mut yapp = yapp::Yapp::new().with_echo_symbol('*');
+    let read = yapp.read_password_with_prompt("Type something and press ENTER within 10s: ");
+    // Dropping the future on timeout cancels reading and restores the terminal.
+    match tokio::time::timeout(Duration::from_secs(10), read).await {
+        Ok(password) => println!("You typed: {}", password?),
+        Err(_) => println!("\nToo slow!"),
+    }
+    Ok(())
+}
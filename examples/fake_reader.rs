@@ -0,0 +1,57 @@
+use std::io;
+use yapp::{IsInteractive, PasswordReader};
+
+fn _tested_func_with_retry_logic<P: PasswordReader + IsInteractive>(
+    yapp: &mut P,
+) -> io::Result<String> {
+    if !yapp.is_interactive() {
+        let password = yapp.read_password()?;
+        return Ok(format!("Sending \"{password}\" to ether"));
+    }
+    let mut password = yapp.read_password_with_prompt("Password: ")?;
+    while password.is_empty() {
+        password = yapp.read_password_with_prompt("Password: ")?;
+    }
+    Ok(format!("Sending \"{password}\" to ether"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yapp::testing::{FakeReader, Key};
+
+    #[test]
+    fn _tested_func_retries_when_interactive() {
+        let mut fake = FakeReader::new()
+            .with_echo_symbol('*')
+            .with_password("")
+            .with_keys([Key::Char('h'), Key::Char('i'), Key::Enter]);
+
+        let result = _tested_func_with_retry_logic(&mut fake);
+
+        assert_eq!("Sending \"hi\" to ether", result.unwrap());
+        fake.assert_prompts(&["Password: ", "Password: "]);
+        assert_eq!(fake.output(), "Password: \nPassword: **\n");
+        fake.assert_finished();
+    }
+
+    #[test]
+    fn _tested_func_does_not_retry_when_not_interactive() {
+        let mut fake = FakeReader::new().with_interactive(false).with_password("");
+
+        let result = _tested_func_with_retry_logic(&mut fake);
+
+        assert_eq!("Sending \"\" to ether", result.unwrap());
+        fake.assert_prompts(&[]);
+    }
+
+    #[test]
+    fn _tested_func_fails_when_cancelled() {
+        let mut fake = FakeReader::new().with_keys([Key::Char('h'), Key::CtrlC]);
+
+        let result = _tested_func_with_retry_logic(&mut fake);
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::Interrupted);
+        fake.assert_finished();
+    }
+}
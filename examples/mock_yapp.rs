@@ -24,14 +24,20 @@ fn _your_func<P: PasswordReader>(yapp: &mut P) -> io::Result<String> {
 mod tests {
     use super::*;
     use mockall::mock;
-    use std::io;
     use yapp::PasswordReader;
 
     mock! {
         Yacc {}
         impl PasswordReader for Yacc {
-            fn read_password(&mut self) -> io::Result<String>;
-            fn read_password_with_prompt(&mut self, prompt: &str) -> io::Result<String>;
+            fn read_password(&mut self) -> yapp::Result<String>;
+            fn read_password_with_prompt(&mut self, prompt: &str) -> yapp::Result<String>;
+            fn read_password_with_confirmation(
+                &mut self,
+                prompt: &str,
+                confirm_prompt: &str,
+                mismatch_message: &str,
+                max_attempts: usize,
+            ) -> yapp::Result<String>;
             fn with_echo_symbol<C>(self, c: C) -> Self
             where
                 C: 'static + Into<Option<char>>;
@@ -19,14 +19,20 @@ fn _tested_func_with_retry_logic<P: PasswordReader + IsInteractive>(
 mod tests {
     use super::*;
     use mockall::mock;
-    use std::io;
     use yapp::{IsInteractive, PasswordReader};
 
     mock! {
         Yacc {}
         impl PasswordReader for Yacc {
-            fn read_password(&mut self) -> io::Result<String>;
-            fn read_password_with_prompt(&mut self, prompt: &str) -> io::Result<String>;
+            fn read_password(&mut self) -> yapp::Result<String>;
+            fn read_password_with_prompt(&mut self, prompt: &str) -> yapp::Result<String>;
+            fn read_password_with_confirmation(
+                &mut self,
+                prompt: &str,
+                confirm_prompt: &str,
+                mismatch_message: &str,
+                max_attempts: usize,
+            ) -> yapp::Result<String>;
             fn with_echo_symbol<C>(self, c: C) -> Self
             where
                 C: 'static + Into<Option<char>>;
@@ -0,0 +1,110 @@
+//! Helpers for the Assuan protocol spoken by pinentry programs.
+
+use crate::SecretString;
+use std::io;
+use zeroize::{Zeroize, Zeroizing};
+
+/// The part of an error code identifying the error, without its source.
+const GPG_ERR_CODE_MASK: u32 = 0xFFFF;
+/// The source part of error codes sent by pinentry programs.
+#[cfg(feature = "pinentry-server")]
+pub(crate) const GPG_ERR_SOURCE_PINENTRY: u32 = 5 << 24;
+/// A failure without a more specific code.
+#[cfg(feature = "pinentry-server")]
+pub(crate) const GPG_ERR_GENERAL: u32 = 1;
+/// The user cancelled the dialog.
+pub(crate) const GPG_ERR_CANCELED: u32 = 99;
+/// The user did not answer in time.
+pub(crate) const GPG_ERR_TIMEOUT: u32 = 62;
+/// The user did not confirm.
+pub(crate) const GPG_ERR_NOT_CONFIRMED: u32 = 114;
+/// The command is not known.
+#[cfg(feature = "pinentry-server")]
+pub(crate) const GPG_ERR_ASS_UNKNOWN_CMD: u32 = 275;
+/// An argument of the command is not valid.
+#[cfg(feature = "pinentry-server")]
+pub(crate) const GPG_ERR_ASS_PARAMETER: u32 = 280;
+
+/// An `ERR` response.
+#[derive(Debug)]
+pub(crate) struct AssuanError {
+    pub(crate) code: u32,
+    pub(crate) message: String,
+}
+
+impl AssuanError {
+    /// Parses the arguments of an `ERR` response, e.g. `83886179 Operation cancelled`.
+    pub(crate) fn parse(args: &str) -> Self {
+        let (code, message) = args.split_once(' ').unwrap_or((args, ""));
+        AssuanError {
+            code: code.parse().unwrap_or(0),
+            message: message.to_owned(),
+        }
+    }
+
+    /// The error code without its source.
+    pub(crate) fn code(&self) -> u32 {
+        self.code & GPG_ERR_CODE_MASK
+    }
+}
+
+/// Escapes the characters which can't appear in an Assuan line.
+pub(crate) fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    encode_into(&mut encoded, text);
+    encoded
+}
+
+/// Escapes a secret like `encode`, into a buffer wiped when dropped.
+#[cfg(feature = "pinentry-server")]
+pub(crate) fn encode_secret(text: &str) -> Zeroizing<String> {
+    // Reserved for every character being escaped, so that the buffer does not need to grow.
+    let mut encoded = Zeroizing::new(String::with_capacity(text.len() * 3));
+    encode_into(&mut encoded, text);
+    encoded
+}
+
+fn encode_into(encoded: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '%' => encoded.push_str("%25"),
+            '\r' => encoded.push_str("%0D"),
+            '\n' => encoded.push_str("%0A"),
+            c => encoded.push(c),
+        }
+    }
+}
+
+/// Unescapes the data of `D` responses and joins it into a secret.
+pub(crate) fn decode(data: &[&str]) -> io::Result<SecretString> {
+    // Decoding never makes the data longer, so the buffer does not need to grow.
+    let mut bytes = Zeroizing::new(Vec::with_capacity(data.iter().map(|line| line.len()).sum()));
+    for line in data {
+        let mut rest = line.as_bytes();
+        while let Some((&b, tail)) = rest.split_first() {
+            if b == b'%' {
+                let byte = tail
+                    .get(..2)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| invalid_data("invalid percent-encoding in Assuan data"))?;
+                bytes.push(byte);
+                rest = &tail[2..];
+            } else {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    match String::from_utf8(std::mem::take(&mut *bytes)) {
+        Ok(text) => Ok(SecretString::from(text)),
+        Err(e) => {
+            e.into_bytes().zeroize();
+            Err(invalid_data("Assuan data is not valid UTF-8"))
+        }
+    }
+}
+
+pub(crate) fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_owned())
+}
@@ -0,0 +1,174 @@
+use crate::{Error, PasswordReader, Result, SecretString, Terminal, Yapp};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::task::spawn_blocking;
+
+/// A future returned by `AsyncPasswordReader` methods.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A trait for reading passwords from the user without blocking an asynchronous runtime.
+///
+/// Its methods mirror those of `PasswordReader`. Dropping a returned future cancels reading, and
+/// the terminal is restored shortly after (on Unix-like systems only, elsewhere reading goes on
+/// in the background until the user presses Enter).
+///
+/// Available with the `async` feature.
+pub trait AsyncPasswordReader {
+    /// Reads a password from the user.
+    fn read_password(&mut self) -> BoxFuture<'_, Result<String>>;
+
+    /// Reads a password from the user with a prompt.
+    fn read_password_with_prompt<'a>(
+        &'a mut self,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, Result<String>>;
+
+    /// Reads a password from the user into a `SecretString`, which wipes its memory when
+    /// dropped.
+    fn read_secret(&mut self) -> BoxFuture<'_, Result<SecretString>>;
+
+    /// Reads a password from the user with a prompt into a `SecretString`, which wipes its
+    /// memory when dropped.
+    fn read_secret_with_prompt<'a>(
+        &'a mut self,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, Result<SecretString>>;
+
+    /// Reads a new password from the user, asking to type it twice.
+    ///
+    /// See `PasswordReader::read_password_with_confirmation`.
+    fn read_password_with_confirmation<'a>(
+        &'a mut self,
+        prompt: &'a str,
+        confirm_prompt: &'a str,
+        mismatch_message: &'a str,
+        max_attempts: usize,
+    ) -> BoxFuture<'a, Result<String>>;
+
+    /// Reads a new password from the user into a `SecretString`, asking to type it twice.
+    ///
+    /// See `PasswordReader::read_password_with_confirmation`.
+    fn read_secret_with_confirmation<'a>(
+        &'a mut self,
+        prompt: &'a str,
+        confirm_prompt: &'a str,
+        mismatch_message: &'a str,
+        max_attempts: usize,
+    ) -> BoxFuture<'a, Result<SecretString>> {
+        let password = self.read_password_with_confirmation(
+            prompt,
+            confirm_prompt,
+            mismatch_message,
+            max_attempts,
+        );
+        Box::pin(async move { password.await.map(SecretString::from) })
+    }
+}
+
+/// Runs blocking reading on tokio's blocking thread pool, so it has to be called within a tokio
+/// runtime.
+impl<T: 'static + Terminal + Send> AsyncPasswordReader for Yapp<T> {
+    fn read_password(&mut self) -> BoxFuture<'_, Result<String>> {
+        self.read_blocking(PasswordReader::read_password)
+    }
+
+    fn read_password_with_prompt<'a>(
+        &'a mut self,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, Result<String>> {
+        let prompt = prompt.to_owned();
+        self.read_blocking(move |yapp| PasswordReader::read_password_with_prompt(yapp, &prompt))
+    }
+
+    fn read_secret(&mut self) -> BoxFuture<'_, Result<SecretString>> {
+        self.read_blocking(PasswordReader::read_secret)
+    }
+
+    fn read_secret_with_prompt<'a>(
+        &'a mut self,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, Result<SecretString>> {
+        let prompt = prompt.to_owned();
+        self.read_blocking(move |yapp| PasswordReader::read_secret_with_prompt(yapp, &prompt))
+    }
+
+    fn read_password_with_confirmation<'a>(
+        &'a mut self,
+        prompt: &'a str,
+        confirm_prompt: &'a str,
+        mismatch_message: &'a str,
+        max_attempts: usize,
+    ) -> BoxFuture<'a, Result<String>> {
+        let prompt = prompt.to_owned();
+        let confirm_prompt = confirm_prompt.to_owned();
+        let mismatch_message = mismatch_message.to_owned();
+        self.read_blocking(move |yapp| {
+            PasswordReader::read_password_with_confirmation(
+                yapp,
+                &prompt,
+                &confirm_prompt,
+                &mismatch_message,
+                max_attempts,
+            )
+        })
+    }
+
+    fn read_secret_with_confirmation<'a>(
+        &'a mut self,
+        prompt: &'a str,
+        confirm_prompt: &'a str,
+        mismatch_message: &'a str,
+        max_attempts: usize,
+    ) -> BoxFuture<'a, Result<SecretString>> {
+        let prompt = prompt.to_owned();
+        let confirm_prompt = confirm_prompt.to_owned();
+        let mismatch_message = mismatch_message.to_owned();
+        self.read_blocking(move |yapp| {
+            PasswordReader::read_secret_with_confirmation(
+                yapp,
+                &prompt,
+                &confirm_prompt,
+                &mismatch_message,
+                max_attempts,
+            )
+        })
+    }
+}
+
+impl<T: 'static + Terminal + Send> Yapp<T> {
+    /// Reads with a copy of this reader on a thread where blocking is allowed. The copy stops
+    /// waiting for input once the returned future is dropped.
+    fn read_blocking<R, F>(&self, read: F) -> BoxFuture<'static, Result<R>>
+    where
+        R: 'static + Send,
+        F: 'static + FnOnce(&mut Yapp<T>) -> Result<R> + Send,
+    {
+        let cancel = CancelOnDrop(Arc::new(AtomicBool::new(false)));
+        let mut yapp = self.clone();
+        // Waiting for keys can only be interrupted on Unix-like systems, elsewhere reading
+        // continues until the user presses Enter.
+        if cfg!(unix) {
+            yapp.cancelled = Some(Arc::clone(&cancel.0));
+        }
+        Box::pin(async move {
+            let _cancel = cancel;
+            match spawn_blocking(move || read(&mut yapp)).await {
+                Ok(result) => result,
+                Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+                Err(e) => Err(Error::Io(io::Error::new(io::ErrorKind::Other, e))),
+            }
+        })
+    }
+}
+
+/// Cancels reading when dropped together with the future.
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,18 @@
+//! A terminal pinentry program built on yapp, e.g. for `gpg-agent`.
+
+use std::process::ExitCode;
+use yapp::{PinentryServer, PromptSink, Yapp};
+
+fn main() -> ExitCode {
+    let yapp = Yapp::new()
+        .with_echo_symbol('*')
+        .with_prompt_sink(PromptSink::Stdout)
+        .with_cancel_on_escape(true);
+    match PinentryServer::new(yapp).serve_stdio() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("yapp-pinentry: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,404 @@
+//! Reads a password for shell scripts, e.g. `password=$(yapp --echo '*' --confirm)`.
+
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::process::ExitCode;
+use std::time::Duration;
+use yapp::{Error, PasswordReader, SecretString, Timeout, Yapp};
+
+const USAGE: &str = "\
+Usage: yapp [OPTIONS]
+
+Asks for a password and prints it to stdout, followed by a newline.
+
+Options:
+  --prompt <TEXT>      Prompt to show [default: \"Password: \"]
+  --echo <CHAR>        Show CHAR for every typed character
+  --confirm            Ask for the password twice
+  --timeout <SECONDS>  Give up when the password is not typed in time
+  --min-length <N>     Ask again when the password is shorter than N characters
+  --tty                Ask on the controlling terminal when stdin is redirected
+  --fd <FD>            Write the password to file descriptor FD instead of stdout
+  -h, --help           Print this help
+  -V, --version        Print the version
+
+Exit status:
+  0    the password was read
+  1    reading or writing failed
+  2    the options are not valid
+  65   the password was not valid, or its confirmation did not match
+  124  the password was not typed in time
+  130  the user cancelled
+";
+
+const CONFIRM_PROMPT: &str = "Repeat password: ";
+const MISMATCH_MESSAGE: &str = "Passwords do not match, try again.";
+const CONFIRM_ATTEMPTS: usize = 3;
+
+/// Exit status for an invalid password (`EX_DATAERR`).
+const EXIT_INVALID: u8 = 65;
+/// Exit status for a timeout, the same as the one of `timeout(1)`.
+const EXIT_TIMED_OUT: u8 = 124;
+/// Exit status for a cancellation, the same as shells use for SIGINT.
+const EXIT_CANCELLED: u8 = 130;
+const EXIT_USAGE: u8 = 2;
+
+#[derive(Debug)]
+struct Options {
+    prompt: String,
+    echo: Option<char>,
+    confirm: bool,
+    timeout: Option<Duration>,
+    min_length: Option<usize>,
+    tty: bool,
+    fd: Option<i32>,
+}
+
+#[derive(Debug)]
+enum Command {
+    Read(Options),
+    Help,
+    Version,
+}
+
+fn main() -> ExitCode {
+    let options = match parse(std::env::args().skip(1)) {
+        Ok(Command::Read(options)) => options,
+        Ok(Command::Help) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Version) => {
+            println!("yapp {}", env!("CARGO_PKG_VERSION"));
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("yapp: {message}\nTry 'yapp --help' for more information.");
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+    match read_into(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("yapp: {e}");
+            ExitCode::from(exit_status(&e))
+        }
+    }
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut options = Options {
+        prompt: String::from("Password: "),
+        echo: None,
+        confirm: false,
+        timeout: None,
+        min_length: None,
+        tty: false,
+        fd: None,
+    };
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_owned())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{name} needs a value"))
+        };
+        match name {
+            "--prompt" => options.prompt = value()?,
+            "--echo" => {
+                let echo = value()?;
+                let mut chars = echo.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => options.echo = Some(c),
+                    _ => return Err(format!("--echo needs a single character, not {echo:?}")),
+                }
+            }
+            "--confirm" => options.confirm = true,
+            "--timeout" => {
+                let seconds: f64 = parse_number(name, &value()?)?;
+                options.timeout = Some(
+                    Duration::try_from_secs_f64(seconds)
+                        .map_err(|_| format!("{name} needs a number of seconds"))?,
+                );
+            }
+            "--min-length" => options.min_length = Some(parse_number(name, &value()?)?),
+            "--tty" => options.tty = true,
+            "--fd" => options.fd = Some(parse_number(name, &value()?)?),
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            _ => return Err(format!("unknown option {arg:?}")),
+        }
+    }
+    Ok(Command::Read(options))
+}
+
+fn parse_number<T>(name: &str, value: &str) -> Result<T, String>
+where
+    T: std::str::FromStr,
+    T::Err: Display,
+{
+    value
+        .parse()
+        .map_err(|e| format!("invalid value {value:?} for {name}: {e}"))
+}
+
+fn read(options: &Options) -> yapp::Result<SecretString> {
+    let mut yapp = Yapp::new()
+        .with_echo_symbol(options.echo)
+        .with_tty(options.tty)
+        .with_cancel_on_escape(true)
+        .with_timeout(options.timeout.map(Timeout::Total));
+    if let Some(min_length) = options.min_length {
+        yapp = yapp.with_validator(move |password| {
+            if password.chars().count() < min_length {
+                Err(format!(
+                    "Password must be at least {min_length} characters long"
+                ))
+            } else {
+                Ok(())
+            }
+        });
+    }
+    if options.confirm {
+        yapp.read_secret_with_confirmation(
+            &options.prompt,
+            CONFIRM_PROMPT,
+            MISMATCH_MESSAGE,
+            CONFIRM_ATTEMPTS,
+        )
+    } else {
+        yapp.read_secret_with_prompt(&options.prompt)
+    }
+}
+
+/// Reads the password and writes it out. The output is opened first, so that the user is not
+/// asked for a password which can't be written.
+fn read_into(options: &Options) -> yapp::Result<()> {
+    let mut output: Box<dyn Write> = match options.fd {
+        Some(fd) => Box::new(open_fd(fd)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let password = read(options)?;
+    writeln!(output, "{}", password.expose_secret())?;
+    Ok(output.flush()?)
+}
+
+#[cfg(unix)]
+fn open_fd(fd: i32) -> io::Result<std::fs::File> {
+    use std::os::unix::io::FromRawFd;
+
+    // A duplicate is written to, so that only descriptors which are open are taken over.
+    let duplicate = unsafe { libc::dup(fd) };
+    if duplicate < 0 {
+        let e = io::Error::last_os_error();
+        return Err(io::Error::new(
+            e.kind(),
+            format!("file descriptor {fd} is not usable: {e}"),
+        ));
+    }
+    Ok(unsafe { std::fs::File::from_raw_fd(duplicate) })
+}
+
+#[cfg(not(unix))]
+fn open_fd(_fd: i32) -> io::Result<std::fs::File> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "file descriptors are only supported on Unix-like systems",
+    ))
+}
+
+fn exit_status(e: &Error) -> u8 {
+    match e {
+        Error::Cancelled => EXIT_CANCELLED,
+        Error::TimedOut => EXIT_TIMED_OUT,
+        Error::Validation(_) | Error::Mismatch { .. } | Error::TooLong { .. } => EXIT_INVALID,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Command, String> {
+        parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn parse_options(args: &[&str]) -> Options {
+        match parse_args(args) {
+            Ok(Command::Read(options)) => options,
+            other => panic!("expected options, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_defaults() {
+        let options = parse_options(&[]);
+
+        assert_eq!(options.prompt, "Password: ");
+        assert_eq!(options.echo, None);
+        assert!(!options.confirm);
+        assert_eq!(options.timeout, None);
+        assert_eq!(options.min_length, None);
+        assert!(!options.tty);
+        assert_eq!(options.fd, None);
+    }
+
+    #[test]
+    fn parse_options_with_separate_and_inline_values() {
+        let options = parse_options(&[
+            "--prompt",
+            "PIN: ",
+            "--echo=*",
+            "--confirm",
+            "--timeout",
+            "1.5",
+            "--min-length=8",
+            "--tty",
+            "--fd",
+            "3",
+        ]);
+
+        assert_eq!(options.prompt, "PIN: ");
+        assert_eq!(options.echo, Some('*'));
+        assert!(options.confirm);
+        assert_eq!(options.timeout, Some(Duration::from_millis(1500)));
+        assert_eq!(options.min_length, Some(8));
+        assert!(options.tty);
+        assert_eq!(options.fd, Some(3));
+    }
+
+    #[test]
+    fn parse_help_and_version() {
+        assert!(matches!(parse_args(&["-h"]), Ok(Command::Help)));
+        assert!(matches!(parse_args(&["--help"]), Ok(Command::Help)));
+        assert!(matches!(parse_args(&["-V"]), Ok(Command::Version)));
+        assert!(matches!(
+            parse_args(&["--confirm", "--version"]),
+            Ok(Command::Version)
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_unknown_options() {
+        assert_eq!(
+            parse_args(&["--colour"]).unwrap_err(),
+            "unknown option \"--colour\""
+        );
+        assert_eq!(
+            parse_args(&["extra"]).unwrap_err(),
+            "unknown option \"extra\""
+        );
+    }
+
+    #[test]
+    fn parse_rejects_missing_values() {
+        assert_eq!(
+            parse_args(&["--prompt"]).unwrap_err(),
+            "--prompt needs a value"
+        );
+        assert_eq!(
+            parse_args(&["--confirm", "--fd"]).unwrap_err(),
+            "--fd needs a value"
+        );
+    }
+
+    #[test]
+    fn parse_rejects_invalid_values() {
+        assert_eq!(
+            parse_args(&["--echo", "**"]).unwrap_err(),
+            "--echo needs a single character, not \"**\""
+        );
+        assert_eq!(
+            parse_args(&["--echo="]).unwrap_err(),
+            "--echo needs a single character, not \"\""
+        );
+        assert_eq!(
+            parse_args(&["--timeout", "-1"]).unwrap_err(),
+            "--timeout needs a number of seconds"
+        );
+        assert_eq!(
+            parse_args(&["--min-length", "eight"]).unwrap_err(),
+            "invalid value \"eight\" for --min-length: invalid digit found in string"
+        );
+        assert!(parse_args(&["--fd=-"])
+            .unwrap_err()
+            .starts_with("invalid value \"-\" for --fd: "));
+    }
+
+    #[test]
+    fn parse_number_reports_the_option() {
+        assert_eq!(parse_number::<usize>("--min-length", "12"), Ok(12));
+        assert_eq!(
+            parse_number::<f64>("--timeout", "soon"),
+            Err(String::from(
+                "invalid value \"soon\" for --timeout: invalid float literal"
+            ))
+        );
+    }
+
+    #[test]
+    fn exit_status_follows_the_documented_codes() {
+        assert_eq!(exit_status(&Error::Validation(String::from("short"))), 65);
+        assert_eq!(exit_status(&Error::Mismatch { attempts: 3 }), 65);
+        assert_eq!(exit_status(&Error::TooLong { max_length: 8 }), 65);
+        assert_eq!(exit_status(&Error::TimedOut), 124);
+        assert_eq!(exit_status(&Error::Cancelled), 130);
+        assert_eq!(
+            exit_status(&Error::NoTerminal(io::ErrorKind::NotFound.into())),
+            1
+        );
+        assert_eq!(exit_status(&Error::Io(io::ErrorKind::BrokenPipe.into())), 1);
+        assert_eq!(EXIT_USAGE, 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn open_fd_writes_to_the_descriptor() {
+        use std::io::{Read, Seek};
+        use std::os::unix::io::AsRawFd;
+
+        let path = std::env::temp_dir().join(format!("yapp-open-fd-{}", std::process::id()));
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        writeln!(open_fd(file.as_raw_fd()).unwrap(), "secret").unwrap();
+
+        let mut written = String::new();
+        file.rewind().unwrap();
+        file.read_to_string(&mut written).unwrap();
+        assert_eq!(written, "secret\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn open_fd_rejects_closed_descriptors() {
+        let e = open_fd(-1).unwrap_err();
+
+        assert!(e
+            .to_string()
+            .starts_with("file descriptor -1 is not usable: "));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn read_into_fails_before_asking_when_fd_is_not_usable() {
+        let mut options = parse_options(&["--fd", "-1"]);
+        options.prompt = String::from("never shown: ");
+
+        let e = read_into(&options).unwrap_err();
+
+        assert!(matches!(e, Error::Io(_)));
+        assert_eq!(exit_status(&e), 1);
+    }
+}
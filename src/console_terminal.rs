@@ -0,0 +1,188 @@
+use crate::sys::KeyInput;
+use crate::terminal::read_stdin;
+use crate::{Key, Terminal};
+use console::Term;
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// The default `Terminal`, built on the `console` crate.
+///
+/// Timeouts are only supported on Unix-like systems, and so is the controlling terminal.
+pub struct ConsoleTerminal {
+    /// The controlling terminal, when opened with `open_tty`.
+    tty: Option<Term>,
+    /// Keeps the terminal from echoing keys while waiting for them with a timeout. Dropped
+    /// together with the terminal, so that the mode is restored after reading a password.
+    key_input: Mutex<Option<KeyInput>>,
+}
+
+impl ConsoleTerminal {
+    /// Creates a terminal on the standard streams.
+    pub const fn new() -> Self {
+        ConsoleTerminal {
+            tty: None,
+            key_input: Mutex::new(None),
+        }
+    }
+
+    /// Returns the handle to write with: stdout, or stderr when stdout is redirected.
+    fn writer(&self) -> Term {
+        if let Some(tty) = &self.tty {
+            return tty.clone();
+        }
+        let term = Term::stdout();
+        if term.is_term() {
+            term
+        } else {
+            Term::stderr()
+        }
+    }
+
+    /// Returns the handle to read keys with.
+    ///
+    /// `console` only reads keys through a handle attached to a terminal, and returns
+    /// `Key::Unknown` otherwise. When both stdout and stderr are redirected, the terminal behind
+    /// stdin is used instead, or the controlling terminal.
+    fn key_reader(&self) -> io::Result<Term> {
+        let term = self.writer();
+        if term.is_term() {
+            return Ok(term);
+        }
+        let term = input_tty()?;
+        if term.is_term() {
+            Ok(term)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no terminal to read keys from",
+            ))
+        }
+    }
+
+    /// Waits at most `timeout` for a key press.
+    fn wait_key(&self, timeout: Duration) -> io::Result<bool> {
+        let mut key_input = self
+            .key_input
+            .lock()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "terminal is poisoned"))?;
+        let key_input = match &mut *key_input {
+            Some(key_input) => key_input,
+            None => key_input.insert(KeyInput::open()?),
+        };
+        key_input.wait(timeout)
+    }
+}
+
+impl Default for ConsoleTerminal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Copies share the controlling terminal, but each waits for keys on its own.
+impl Clone for ConsoleTerminal {
+    fn clone(&self) -> Self {
+        ConsoleTerminal {
+            tty: self.tty.clone(),
+            key_input: Mutex::new(None),
+        }
+    }
+}
+
+impl fmt::Debug for ConsoleTerminal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConsoleTerminal")
+            .field("tty", &self.tty.is_some())
+            .finish()
+    }
+}
+
+impl Terminal for ConsoleTerminal {
+    fn is_terminal(&self) -> bool {
+        self.tty.is_some() || io::stdin().is_terminal()
+    }
+
+    #[cfg(unix)]
+    fn open_tty(&self) -> io::Result<Self> {
+        let tty = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")?;
+        Ok(ConsoleTerminal {
+            tty: Some(Term::read_write_pair(tty.try_clone()?, tty)),
+            key_input: Mutex::new(None),
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn open_tty(&self) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "controlling terminal is only supported on Unix-like systems",
+        ))
+    }
+
+    fn read_key(&self, timeout: Option<Duration>) -> io::Result<Option<Key>> {
+        if let Some(timeout) = timeout {
+            if !self.wait_key(timeout)? {
+                return Ok(None);
+            }
+        }
+        Ok(Some(from_console(self.key_reader()?.read_key_raw()?)))
+    }
+
+    fn read_input(&self, buf: &mut [u8], timeout: Option<Duration>) -> io::Result<Option<usize>> {
+        read_stdin(buf, timeout)
+    }
+
+    fn write_str(&self, text: &str) -> io::Result<()> {
+        let tty = self.writer();
+        tty.write_str(text)?;
+        tty.flush()
+    }
+}
+
+/// Opens the terminal behind stdin, or the controlling terminal when stdin is redirected.
+#[cfg(unix)]
+fn input_tty() -> io::Result<Term> {
+    use std::os::unix::io::AsFd;
+
+    let tty = if io::stdin().is_terminal() {
+        std::fs::File::from(io::stdin().as_fd().try_clone_to_owned()?)
+    } else {
+        std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")?
+    };
+    Ok(Term::read_write_pair(tty.try_clone()?, tty))
+}
+
+#[cfg(not(unix))]
+fn input_tty() -> io::Result<Term> {
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no terminal to read keys from",
+    ))
+}
+
+fn from_console(key: console::Key) -> Key {
+    match key {
+        console::Key::Char(c) => Key::Char(c),
+        console::Key::Enter => Key::Enter,
+        console::Key::Escape => Key::Escape,
+        console::Key::Backspace => Key::Backspace,
+        console::Key::Del => Key::Delete,
+        console::Key::Tab => Key::Tab,
+        console::Key::Home => Key::Home,
+        console::Key::End => Key::End,
+        console::Key::ArrowLeft => Key::ArrowLeft,
+        console::Key::ArrowRight => Key::ArrowRight,
+        console::Key::ArrowUp => Key::ArrowUp,
+        console::Key::ArrowDown => Key::ArrowDown,
+        console::Key::CtrlC => Key::CtrlC,
+        _ => Key::Unknown,
+    }
+}
@@ -0,0 +1,203 @@
+use crate::terminal::{read_stdin, write_text};
+use crate::{Key, Terminal};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, IsTerminal};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A `Terminal` built on the `crossterm` crate, for applications which already use it.
+///
+/// Raw mode is enabled when the first key is read, and disabled when the `CrosstermTerminal` is
+/// dropped, so that nothing typed or pasted between key presses is echoed. `Yapp` reads every
+/// password with its own copy. Raw mode is left alone when the application has already enabled
+/// it. When stdin is redirected, `crossterm` reads keys from the controlling terminal.
+///
+/// Available with the `crossterm` feature.
+pub struct CrosstermTerminal {
+    /// The controlling terminal, when opened with `open_tty`.
+    tty: Option<Arc<File>>,
+    /// Keeps raw mode enabled until the terminal is dropped.
+    raw_mode: Mutex<Option<RawMode>>,
+}
+
+impl CrosstermTerminal {
+    /// Creates a terminal on the standard streams.
+    pub const fn new() -> Self {
+        CrosstermTerminal {
+            tty: None,
+            raw_mode: Mutex::new(None),
+        }
+    }
+}
+
+impl Default for CrosstermTerminal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Copies share the controlling terminal, but each enables raw mode on its own.
+impl Clone for CrosstermTerminal {
+    fn clone(&self) -> Self {
+        CrosstermTerminal {
+            tty: self.tty.clone(),
+            raw_mode: Mutex::new(None),
+        }
+    }
+}
+
+impl fmt::Debug for CrosstermTerminal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CrosstermTerminal")
+            .field("tty", &self.tty.is_some())
+            .finish()
+    }
+}
+
+impl Terminal for CrosstermTerminal {
+    fn is_terminal(&self) -> bool {
+        self.tty.is_some() || io::stdin().is_terminal()
+    }
+
+    #[cfg(unix)]
+    fn open_tty(&self) -> io::Result<Self> {
+        let tty = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")?;
+        Ok(CrosstermTerminal {
+            tty: Some(Arc::new(tty)),
+            raw_mode: Mutex::new(None),
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn open_tty(&self) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "controlling terminal is only supported on Unix-like systems",
+        ))
+    }
+
+    fn read_key(&self, timeout: Option<Duration>) -> io::Result<Option<Key>> {
+        let mut raw_mode = self
+            .raw_mode
+            .lock()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "terminal is poisoned"))?;
+        if raw_mode.is_none() {
+            *raw_mode = Some(RawMode::enable()?);
+        }
+        // A timeout too long to be reached is the same as none.
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        loop {
+            if let Some(deadline) = deadline {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if !event::poll(remaining)? {
+                    return Ok(None);
+                }
+            }
+            // Other events, like resizing the terminal or releasing a key, are skipped.
+            if let Event::Key(event) = event::read()? {
+                if event.kind != KeyEventKind::Release {
+                    return Ok(Some(from_crossterm(event)));
+                }
+            }
+        }
+    }
+
+    fn read_input(&self, buf: &mut [u8], timeout: Option<Duration>) -> io::Result<Option<usize>> {
+        read_stdin(buf, timeout)
+    }
+
+    fn write_str(&self, text: &str) -> io::Result<()> {
+        write_text(self.tty.as_deref(), text)
+    }
+}
+
+/// Enables raw mode, unless it is already enabled, and restores it when dropped.
+struct RawMode {
+    enabled: bool,
+}
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        if terminal::is_raw_mode_enabled()? {
+            return Ok(RawMode { enabled: false });
+        }
+        terminal::enable_raw_mode()?;
+        let raw_mode = RawMode { enabled: true };
+        #[cfg(unix)]
+        keep_output_processing()?;
+        Ok(raw_mode)
+    }
+}
+
+/// Turns output processing back on after raw mode turned it off, so that line breaks written
+/// while raw mode is enabled still move the cursor to the start of the line, like with `console`.
+#[cfg(unix)]
+fn keep_output_processing() -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    // The same terminal as `crossterm` switches to raw mode.
+    let tty;
+    let fd = if io::stdin().is_terminal() {
+        libc::STDIN_FILENO
+    } else {
+        tty = File::open("/dev/tty")?;
+        tty.as_raw_fd()
+    };
+    let mut termios = std::mem::MaybeUninit::uninit();
+    if unsafe { libc::tcgetattr(fd, termios.as_mut_ptr()) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut termios = unsafe { termios.assume_init() };
+    termios.c_oflag |= libc::OPOST;
+    if unsafe { libc::tcsetattr(fd, libc::TCSADRAIN, &termios) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if self.enabled {
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+}
+
+/// Converts a key event into the key `console` reports for the same input, so that both
+/// backends behave the same. Control characters which `console` maps to keys (e.g. Ctrl-A to
+/// Home) are mapped the same way, and other Ctrl-letter combinations to control characters.
+pub(crate) fn from_crossterm(event: KeyEvent) -> Key {
+    match event.code {
+        KeyCode::Char(c) if event.modifiers.contains(KeyModifiers::CONTROL) => {
+            match c.to_ascii_lowercase() {
+                'a' => Key::Home,
+                'c' => Key::CtrlC,
+                'e' => Key::End,
+                'h' => Key::Backspace,
+                'i' => Key::Tab,
+                'j' | 'm' => Key::Enter,
+                c @ 'a'..='z' => Key::Char(char::from(c as u8 & 0x1f)),
+                _ => Key::Unknown,
+            }
+        }
+        KeyCode::Char(c) => Key::Char(c),
+        KeyCode::Enter => Key::Enter,
+        KeyCode::Esc => Key::Escape,
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Delete => Key::Delete,
+        KeyCode::Tab => Key::Tab,
+        KeyCode::Home => Key::Home,
+        KeyCode::End => Key::End,
+        KeyCode::Left => Key::ArrowLeft,
+        KeyCode::Right => Key::ArrowRight,
+        KeyCode::Up => Key::ArrowUp,
+        KeyCode::Down => Key::ArrowDown,
+        _ => Key::Unknown,
+    }
+}
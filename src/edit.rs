@@ -0,0 +1,127 @@
+use crate::Key;
+use crate::SecretString;
+
+/// Ctrl-U, clears the whole line.
+const CTRL_U: char = '\u{15}';
+/// Ctrl-W, deletes the word before the cursor.
+const CTRL_W: char = '\u{17}';
+/// Ctrl-D, cancels reading when nothing has been typed.
+const CTRL_D: char = '\u{4}';
+
+/// What the reader should do after a key has been handled.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Action {
+    /// Keep reading keys.
+    Continue,
+    /// The key was not accepted, e.g. because the maximum length has been reached. Keep reading
+    /// keys.
+    Rejected,
+    /// The user has finished typing.
+    Submit,
+    /// The user has given up typing.
+    Cancel,
+}
+
+/// A password being typed, with a cursor which can be moved within it.
+///
+/// Supports the usual line-editing bindings: Left, Right, Home (Ctrl-A), End (Ctrl-E),
+/// Backspace, Delete, Ctrl-U and Ctrl-W. Ctrl-C, Ctrl-D on an empty line and optionally Escape
+/// cancel reading.
+pub(crate) struct LineEditor {
+    input: SecretString,
+    /// Byte offset of the cursor in `input`.
+    cursor: usize,
+    cancel_on_escape: bool,
+    max_length: Option<usize>,
+}
+
+impl LineEditor {
+    pub(crate) fn new(cancel_on_escape: bool, max_length: Option<usize>) -> Self {
+        LineEditor {
+            input: SecretString::new(),
+            cursor: 0,
+            cancel_on_escape,
+            max_length,
+        }
+    }
+
+    pub(crate) fn handle_key(&mut self, key: Key) -> Action {
+        match key {
+            Key::Enter => return Action::Submit,
+            Key::CtrlC => return Action::Cancel,
+            Key::Char(CTRL_D) if self.input.is_empty() => return Action::Cancel,
+            Key::Escape if self.cancel_on_escape => return Action::Cancel,
+            Key::Char(CTRL_U) => self.delete(0..self.input.len()),
+            Key::Char(CTRL_W) => self.delete(self.previous_word()..self.cursor),
+            Key::Char(c) if !c.is_control() => {
+                if self
+                    .max_length
+                    .is_some_and(|max_length| self.len() >= max_length)
+                {
+                    return Action::Rejected;
+                }
+                self.input.insert(self.cursor, c);
+                self.cursor += c.len_utf8();
+            }
+            Key::Backspace => self.delete(self.previous_char()..self.cursor),
+            Key::Delete => self.delete(self.cursor..self.next_char()),
+            Key::ArrowLeft => self.cursor = self.previous_char(),
+            Key::ArrowRight => self.cursor = self.next_char(),
+            Key::Home => self.cursor = 0,
+            Key::End => self.cursor = self.input.len(),
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    /// Number of characters typed.
+    pub(crate) fn len(&self) -> usize {
+        self.input.expose_secret().chars().count()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+
+    pub(crate) fn expose_secret(&self) -> &str {
+        self.input.expose_secret()
+    }
+
+    /// Number of characters before the cursor.
+    pub(crate) fn cursor(&self) -> usize {
+        self.input.expose_secret()[..self.cursor].chars().count()
+    }
+
+    pub(crate) fn into_secret(self) -> SecretString {
+        self.input
+    }
+
+    fn delete(&mut self, range: std::ops::Range<usize>) {
+        self.cursor = range.start;
+        self.input.remove(range);
+    }
+
+    fn previous_char(&self) -> usize {
+        self.input.expose_secret()[..self.cursor]
+            .chars()
+            .next_back()
+            .map_or(0, |c| self.cursor - c.len_utf8())
+    }
+
+    fn next_char(&self) -> usize {
+        self.input.expose_secret()[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+
+    /// Finds the start of the word before the cursor, skipping whitespace like `readline` does.
+    fn previous_word(&self) -> usize {
+        let before = self.input.expose_secret()[..self.cursor].trim_end();
+        before
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8())
+    }
+}
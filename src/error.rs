@@ -0,0 +1,99 @@
+use std::fmt;
+use std::io;
+
+/// A `Result` of reading a password.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// An error returned when reading a password failed.
+///
+/// Converts into an `io::Error` of a matching kind (e.g. `io::ErrorKind::Interrupted` for
+/// `Cancelled`), so `?` keeps working in functions returning `io::Result`. Converting such an
+/// `io::Error` back returns the original `Error`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The user cancelled reading with Ctrl-C, Ctrl-D or Escape.
+    Cancelled,
+    /// The user did not type the password in time (see `Yapp::with_timeout`).
+    TimedOut,
+    /// The password did not pass validation (see `Yapp::with_validator` and
+    /// `Yapp::with_min_strength`). Contains the message for the user.
+    Validation(String),
+    /// The controlling terminal was requested (see `Yapp::with_tty`) but could not be opened.
+    NoTerminal(io::Error),
+    /// The password and its confirmation did not match in any of the attempts.
+    Mismatch {
+        /// Number of attempts made.
+        attempts: usize,
+    },
+    /// Redirected input is longer than the maximum length (see `Yapp::with_max_length`).
+    TooLong {
+        /// The maximum number of characters allowed.
+        max_length: usize,
+    },
+    /// Reading or writing failed.
+    Io(io::Error),
+}
+
+impl Error {
+    /// The kind of the `io::Error` this error converts into.
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Error::Cancelled => io::ErrorKind::Interrupted,
+            Error::TimedOut => io::ErrorKind::TimedOut,
+            Error::Validation(_) | Error::Mismatch { .. } | Error::TooLong { .. } => {
+                io::ErrorKind::InvalidData
+            }
+            Error::NoTerminal(_) => io::ErrorKind::NotFound,
+            Error::Io(e) => e.kind(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Cancelled => f.write_str("cancelled by the user"),
+            Error::TimedOut => f.write_str("timed out waiting for the password"),
+            Error::Validation(message) => write!(f, "invalid password: {message}"),
+            Error::NoTerminal(e) => write!(f, "no terminal available: {e}"),
+            Error::Mismatch { attempts } => {
+                write!(f, "passwords did not match after {attempts} attempt(s)")
+            }
+            Error::TooLong { max_length } => {
+                write!(f, "password is longer than {max_length} characters")
+            }
+            Error::Io(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::NoTerminal(e) | Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        if !e.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            return Error::Io(e);
+        }
+        match e.into_inner().map(|inner| inner.downcast::<Error>()) {
+            Some(Ok(error)) => *error,
+            _ => unreachable!("the io::Error wraps an Error"),
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Io(e) => e,
+            e => io::Error::new(e.kind(), e),
+        }
+    }
+}
@@ -16,12 +16,61 @@
 //!
 //! * Reads user passwords from the input, optionally with a prompt and
 //!   echoing replacement symbols (`*`, or another of your choice).
+//! * Optionally echoes passwords without revealing their length: a fixed indicator, a random
+//!   number of symbols per character, a character counter, or showing the last character
+//!   briefly (see `Mask`).
+//! * Optionally returns passwords as a `SecretString`, which wipes its memory when dropped and
+//!   never reveals its content in `Debug` or `Display` output.
+//! * Asks for a new password twice and compares the entries, re-prompting on mismatch.
+//! * Optionally validates passwords with your own check (e.g. minimum length), printing its
+//!   message and asking again in interactive mode.
+//! * Optionally shows the estimated strength of a new password while it is typed, and rejects
+//!   passwords which are too weak (see `Strength`).
+//! * Supports line editing while typing: Left, Right, Home (Ctrl-A), End (Ctrl-E), Backspace,
+//!   Delete, Ctrl-U (clear the line) and Ctrl-W (delete the previous word).
+//! * Optionally shows the password in plain text while it is typed, toggled with a key of your
+//!   choice (see `Yapp::with_reveal_key`).
+//! * Ctrl-C, Ctrl-D on an empty line and optionally Escape cancel reading, returning
+//!   `Error::Cancelled`.
+//! * Returns a `yapp::Error` telling cancellation, timeout, failed validation, a missing terminal
+//!   and mismatched confirmation apart from I/O failures. It converts into an `io::Error` for
+//!   code written against earlier versions.
 //! * Reads passwords interactively or non-interactively (e.g. when input is redirected through
 //!   a pipe).
+//! * Writes prompts and echoed symbols to stderr by default, so they don't mix with the output of
+//!   your program. Stdout, the controlling terminal or any `Write` can be used instead (see
+//!   `PromptSink`).
+//! * Optionally limits the length of passwords, so that a pasted file or a malicious pipe can't
+//!   make it allocate unbounded memory.
+//! * Optionally gives up waiting for the user after a timeout, either for the whole password or
+//!   between key presses (Unix-like systems only).
+//! * Optionally takes passwords from an environment variable, a file or an inherited file
+//!   descriptor before asking the user, e.g. in CI (see `Source`).
+//! * Optionally runs an askpass program (`SSH_ASKPASS`, `SUDO_ASKPASS` or your own) to ask for
+//!   passwords on desktops without a terminal.
+//! * Asks for passwords with a `pinentry` program over the Assuan protocol, like GnuPG does (see
+//!   `Pinentry`).
+//! * Optionally reads from the controlling terminal (`/dev/tty`) when stdin is redirected, like
+//!   `sudo` or `ssh` do.
+//! * With the `async` feature, reads passwords without blocking a tokio runtime through the
+//!   `AsyncPasswordReader` trait. Dropping the returned future cancels reading.
+//! * With the `cli` feature, builds `yapp`, a command-line program reading passwords for shell
+//!   scripts (see `yapp --help`).
+//! * With the `pinentry-server` feature, builds `yapp-pinentry`, a terminal pinentry program
+//!   for `gpg-agent` (see `PinentryServer`).
+//! * Reads keys through a pluggable `Terminal` backend, `ConsoleTerminal` by default. Another
+//!   one, e.g. a fake terminal in tests, can be set with `Yapp::with_terminal`.
+//! * With the `termios` feature and `console` (a default feature) disabled, reads keys using
+//!   termios directly, without the dependencies of `console` (see `TermiosTerminal`, Unix-like
+//!   systems only).
+//! * With the `crossterm` feature, reads keys with `crossterm` instead of `console` (see
+//!   `CrosstermTerminal`), for applications which already use it.
 //! * Using the `PasswordReader` (optionally `PasswordReader + IsInteractive`) trait in your code
 //!   allows for mocking the entire library in tests
 //!   (see an [example1](https://github.com/Caleb9/yapp/blob/main/examples/mock_yapp.rs) and
-//!   [example2](https://github.com/Caleb9/yapp/blob/main/examples/mock_yapp_with_is_interactive.rs))
+//!   [example2](https://github.com/Caleb9/yapp/blob/main/examples/mock_yapp_with_is_interactive.rs)),
+//!   or for using the scripted `testing::FakeReader` with the `testing` feature
+//!   (see [example3](https://github.com/Caleb9/yapp/blob/main/examples/fake_reader.rs))
 //! * Thanks to using the `console` library underneath, it handles unicode
 //!   correctly (tested on Windows and Linux).
 //!
@@ -47,30 +96,118 @@
 //!
 //! See [examples](https://github.com/Caleb9/yapp/tree/main/examples) for more.
 
-use console::Key;
+use edit::{Action, LineEditor};
+use mask::Masking;
+use screen::Screen;
+use secret::SecretReader;
+use sink::Output;
 use std::io::{self, Write};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use timeout::{TimedReader, Waiter};
+use validate::Validator;
+use zeroize::Zeroizing;
 
-#[cfg(not(test))]
-use {
-    console::Term,
-    std::io::{stdin, stdout, IsTerminal},
-};
-
-#[cfg(test)]
-use tests::mocks::{stdin, stdout, TermMock as Term};
+#[cfg(feature = "async")]
+pub use async_reader::{AsyncPasswordReader, BoxFuture};
+#[cfg(feature = "console")]
+pub use console_terminal::ConsoleTerminal;
+#[cfg(feature = "crossterm")]
+pub use crossterm_terminal::CrosstermTerminal;
+pub use error::{Error, Result};
+pub use mask::Mask;
+pub use pinentry::Pinentry;
+#[cfg(feature = "pinentry-server")]
+pub use pinentry_server::PinentryServer;
+pub use secret::SecretString;
+pub use sink::PromptSink;
+pub use source::Source;
+pub use strength::Strength;
+pub use terminal::{DefaultTerminal, Key, Terminal};
+#[cfg(all(unix, feature = "termios"))]
+pub use termios_terminal::TermiosTerminal;
+pub use timeout::Timeout;
 
+mod assuan;
+#[cfg(feature = "async")]
+mod async_reader;
+#[cfg(feature = "console")]
+mod console_terminal;
+#[cfg(feature = "crossterm")]
+mod crossterm_terminal;
+mod edit;
+mod error;
+mod mask;
+mod pinentry;
+#[cfg(feature = "pinentry-server")]
+mod pinentry_server;
+mod screen;
+mod secret;
+mod sink;
+mod source;
+mod strength;
+mod sys;
+mod terminal;
+#[cfg(all(unix, feature = "termios"))]
+mod termios_terminal;
+#[cfg(feature = "testing")]
+pub mod testing;
 #[cfg(test)]
 mod tests;
+mod timeout;
+mod validate;
 
 /// A trait for reading passwords from the user.
 ///
 /// Use the `new` function to obtain a new instance
 pub trait PasswordReader {
     /// Reads a password from the user.
-    fn read_password(&mut self) -> io::Result<String>;
+    fn read_password(&mut self) -> Result<String>;
 
     /// Reads a password from the user with a prompt.
-    fn read_password_with_prompt(&mut self, prompt: &str) -> io::Result<String>;
+    fn read_password_with_prompt(&mut self, prompt: &str) -> Result<String>;
+
+    /// Reads a password from the user into a `SecretString`, which wipes its memory when
+    /// dropped.
+    fn read_secret(&mut self) -> Result<SecretString> {
+        self.read_password().map(SecretString::from)
+    }
+
+    /// Reads a password from the user with a prompt into a `SecretString`, which wipes its
+    /// memory when dropped.
+    fn read_secret_with_prompt(&mut self, prompt: &str) -> Result<SecretString> {
+        self.read_password_with_prompt(prompt)
+            .map(SecretString::from)
+    }
+
+    /// Reads a new password from the user, asking to type it twice.
+    ///
+    /// The user is prompted with `prompt` and then with `confirm_prompt`. When the entries
+    /// differ, `mismatch_message` is printed and the user is asked again. After `max_attempts`
+    /// unsuccessful attempts (at least one attempt is always made), `Error::Mismatch` is
+    /// returned.
+    fn read_password_with_confirmation(
+        &mut self,
+        prompt: &str,
+        confirm_prompt: &str,
+        mismatch_message: &str,
+        max_attempts: usize,
+    ) -> Result<String>;
+
+    /// Reads a new password from the user into a `SecretString`, asking to type it twice.
+    ///
+    /// See `read_password_with_confirmation`.
+    fn read_secret_with_confirmation(
+        &mut self,
+        prompt: &str,
+        confirm_prompt: &str,
+        mismatch_message: &str,
+        max_attempts: usize,
+    ) -> Result<SecretString> {
+        self.read_password_with_confirmation(prompt, confirm_prompt, mismatch_message, max_attempts)
+            .map(SecretString::from)
+    }
 
     /// Sets the echoed replacement symbol for the password characters.
     ///
@@ -91,86 +228,670 @@ pub trait IsInteractive {
 
 /// Creates a new password reader. Returns an instance of `PasswordReader` trait.
 pub fn new() -> impl PasswordReader + IsInteractive {
-    Yapp::default()
+    Yapp::new()
+}
+
+/// Rings the terminal bell.
+const BELL: char = '\x07';
+
+/// Bytes taken by the longest strength meter echoed after the password, ` [very strong]`.
+const STRENGTH_METER_CAPACITY: usize = " [very strong]".len();
+
+/// Where a password comes from.
+enum Input<T> {
+    /// Taken from a non-interactive source.
+    Taken(SecretString),
+    /// To be asked for with the given reader.
+    Ask(Yapp<T>),
 }
 
 /// An implementation of the `PasswordReader` trait.
-#[derive(Debug, Default, Copy, Clone)]
-pub struct Yapp {
-    echo_symbol: Option<char>,
+///
+/// Reads from the `DefaultTerminal` by default, another `Terminal` can be set with
+/// `with_terminal`.
+#[derive(Debug, Clone)]
+pub struct Yapp<T = DefaultTerminal> {
+    terminal: T,
+    mask: Mask,
+    use_tty: bool,
+    prompt_sink: PromptSink,
+    preserve_line_ending: bool,
+    cancel_on_escape: bool,
+    reveal_key: Option<Key>,
+    timeout: Option<Timeout>,
+    max_length: Option<usize>,
+    bell: bool,
+    validator: Option<Validator>,
+    validation_attempts: usize,
+    strength_meter: bool,
+    min_strength: Option<Strength>,
+    /// Set when the reading should stop, e.g. because an asynchronous read has been dropped.
+    cancelled: Option<Arc<AtomicBool>>,
+    sources: Vec<Source>,
+    /// Looks up the environment variables of `Source::Env`.
+    env_var: fn(&str) -> std::result::Result<String, std::env::VarError>,
 }
 
-impl PasswordReader for Yapp {
-    fn read_password(&mut self) -> io::Result<String> {
-        if self.is_interactive() {
-            self.read_interactive()
-        } else {
-            self.read_non_interactive()
-        }
+impl<T: Terminal> PasswordReader for Yapp<T> {
+    fn read_password(&mut self) -> Result<String> {
+        self.read_validated(None).map(SecretString::into_string)
+    }
+
+    fn read_password_with_prompt(&mut self, prompt: &str) -> Result<String> {
+        self.read_validated(Some(prompt))
+            .map(SecretString::into_string)
+    }
+
+    fn read_secret(&mut self) -> Result<SecretString> {
+        self.read_validated(None)
     }
 
-    fn read_password_with_prompt(&mut self, prompt: &str) -> io::Result<String> {
-        write!(stdout(), "{prompt}")?;
-        stdout().flush()?;
-        self.read_password()
+    fn read_secret_with_prompt(&mut self, prompt: &str) -> Result<SecretString> {
+        self.read_validated(Some(prompt))
     }
 
-    fn with_echo_symbol<C>(mut self, s: C) -> Self
+    fn read_password_with_confirmation(
+        &mut self,
+        prompt: &str,
+        confirm_prompt: &str,
+        mismatch_message: &str,
+        max_attempts: usize,
+    ) -> Result<String> {
+        self.read_secret_with_confirmation(prompt, confirm_prompt, mismatch_message, max_attempts)
+            .map(SecretString::into_string)
+    }
+
+    fn read_secret_with_confirmation(
+        &mut self,
+        prompt: &str,
+        confirm_prompt: &str,
+        mismatch_message: &str,
+        max_attempts: usize,
+    ) -> Result<SecretString> {
+        let yapp = match self.input(Some(prompt))? {
+            Input::Taken(password) => return self.checked(password),
+            Input::Ask(yapp) => yapp,
+        };
+        let attempts = max_attempts.max(1);
+        for _ in 0..attempts {
+            let password = yapp.ask_validated(Some(prompt))?;
+            yapp.print(confirm_prompt)?;
+            let confirmation = yapp.read(false)?;
+            if password == confirmation {
+                return Ok(password);
+            }
+            yapp.print(&format!("{mismatch_message}\n"))?;
+        }
+        Err(Error::Mismatch { attempts })
+    }
+
+    fn with_echo_symbol<C>(self, s: C) -> Self
     where
         C: Into<Option<char>>,
     {
-        self.echo_symbol = s.into();
-        self
+        Yapp::with_echo_symbol(self, s)
     }
 }
 
-impl IsInteractive for Yapp {
+impl<T: Terminal> IsInteractive for Yapp<T> {
     fn is_interactive(&self) -> bool {
-        stdin().is_terminal()
+        self.terminal.is_terminal() || matches!(self.tty(), Ok(Some(_)))
+    }
+}
+
+impl Default for Yapp {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Yapp {
     /// Create new Yapp instance without echo symbol
     pub const fn new() -> Self {
-        Yapp { echo_symbol: None }
+        Yapp {
+            terminal: DefaultTerminal::new(),
+            mask: Mask::Hidden,
+            use_tty: false,
+            prompt_sink: PromptSink::Stderr,
+            preserve_line_ending: false,
+            cancel_on_escape: false,
+            reveal_key: None,
+            timeout: None,
+            max_length: None,
+            bell: false,
+            validator: None,
+            validation_attempts: 3,
+            strength_meter: false,
+            min_strength: None,
+            cancelled: None,
+            sources: Vec::new(),
+            env_var: |name| std::env::var(name),
+        }
+    }
+}
+
+impl<T: Terminal> Yapp<T> {
+    /// Sets the terminal backend to read passwords from, e.g. a fake one in tests.
+    pub fn with_terminal<U: Terminal>(self, terminal: U) -> Yapp<U> {
+        Yapp {
+            terminal,
+            mask: self.mask,
+            use_tty: self.use_tty,
+            prompt_sink: self.prompt_sink,
+            preserve_line_ending: self.preserve_line_ending,
+            cancel_on_escape: self.cancel_on_escape,
+            reveal_key: self.reveal_key,
+            timeout: self.timeout,
+            max_length: self.max_length,
+            bell: self.bell,
+            validator: self.validator,
+            validation_attempts: self.validation_attempts,
+            strength_meter: self.strength_meter,
+            min_strength: self.min_strength,
+            cancelled: self.cancelled,
+            sources: self.sources,
+            env_var: self.env_var,
+        }
+    }
+
+    /// Sets the echoed replacement symbol for the password characters.
+    ///
+    /// Set to None to not echo any characters. The same as `PasswordReader::with_echo_symbol`,
+    /// available without importing the trait (e.g. when only `AsyncPasswordReader` is used).
+    pub fn with_echo_symbol<C>(mut self, s: C) -> Self
+    where
+        C: Into<Option<char>>,
+    {
+        self.mask = Mask::from(s.into());
+        self
+    }
+
+    /// Sets how the password is echoed, e.g. without revealing its length (see `Mask`).
+    ///
+    /// Replaces the echo symbol, `with_echo_symbol('*')` is the same as
+    /// `with_mask(Mask::Symbol('*'))`. Defaults to `Mask::Hidden`.
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Sets where passwords are taken from, in order of preference.
+    ///
+    /// The first available `Source` is used. When none is (or no sources are set, the default),
+    /// the user is asked as usual. A password taken from an environment variable, a file, a file
+    /// descriptor or an askpass program is not confirmed, and fails with `Error::Validation`
+    /// straight away when it does not pass validation.
+    ///
+    /// ```rust
+    /// use yapp::Source;
+    ///
+    /// let yapp = yapp::Yapp::new().with_sources([
+    ///     Source::env("MYTOOL_PASSWORD"),
+    ///     Source::file("/run/secrets/mytool"),
+    ///     Source::Tty,
+    ///     Source::Askpass(None),
+    /// ]);
+    /// ```
+    pub fn with_sources<I>(mut self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = Source>,
+    {
+        self.sources = sources.into_iter().collect();
+        self
+    }
+
+    /// Sets a check which passwords have to pass.
+    ///
+    /// The validator returns a message for the user when a password is not acceptable. In
+    /// interactive mode, the message is printed and the user is asked again, up to the number of
+    /// times set with `with_validation_attempts` (3 by default). In non-interactive mode, or when
+    /// the attempts run out, `Error::Validation` is returned.
+    ///
+    /// ```rust
+    /// let yapp = yapp::Yapp::new().with_validator(|password| {
+    ///     if password.chars().count() < 12 {
+    ///         Err(String::from("Password must be at least 12 characters long"))
+    ///     } else {
+    ///         Ok(())
+    ///     }
+    /// });
+    /// ```
+    pub fn with_validator<F>(mut self, validator: F) -> Self
+    where
+        F: 'static + Fn(&str) -> Result<(), String> + Send + Sync,
+    {
+        self.validator = Some(Validator::new(validator));
+        self
+    }
+
+    /// Sets how many times the user is asked for a password which passes validation in
+    /// interactive mode. At least one attempt is always made.
+    pub fn with_validation_attempts(mut self, validation_attempts: usize) -> Self {
+        self.validation_attempts = validation_attempts;
+        self
+    }
+
+    /// Shows the estimated `Strength` of the password to the right of the echoed symbols while
+    /// it is typed in interactive mode. The password itself is not revealed.
+    pub fn with_strength_meter(mut self, strength_meter: bool) -> Self {
+        self.strength_meter = strength_meter;
+        self
+    }
+
+    /// Sets the minimum `Strength` of passwords.
+    ///
+    /// In interactive mode, pressing Enter does nothing (other than ringing the bell if enabled)
+    /// until the password is strong enough. In non-interactive mode, `Error::Validation` is
+    /// returned for a weaker password.
+    ///
+    /// When asking for a password with confirmation, only the first entry is checked.
+    pub fn with_min_strength<S: Into<Option<Strength>>>(mut self, min_strength: S) -> Self {
+        self.min_strength = min_strength.into();
+        self
+    }
+
+    /// Sets the maximum number of characters of a password.
+    ///
+    /// In interactive mode, further characters are not accepted once the limit is reached. In
+    /// non-interactive mode, reading stops and fails with `Error::TooLong` when the input is
+    /// longer. Set to `None` (the default) for no limit.
+    pub fn with_max_length<L>(mut self, max_length: L) -> Self
+    where
+        L: Into<Option<usize>>,
+    {
+        self.max_length = max_length.into();
+        self
+    }
+
+    /// Rings the terminal bell when a key press is rejected in interactive mode, e.g. because
+    /// the maximum length has been reached.
+    pub fn with_bell(mut self, bell: bool) -> Self {
+        self.bell = bell;
+        self
+    }
+
+    /// Sets how long reading a password may wait for the user.
+    ///
+    /// Applies to both interactive and non-interactive reading. When the time runs out, the
+    /// terminal is restored and `Error::TimedOut` is returned. Set to `None` (the default) to wait
+    /// indefinitely.
+    ///
+    /// Timeouts are only supported on Unix-like systems, elsewhere reading fails with `Error::Io`
+    /// of `std::io::ErrorKind::Unsupported` kind when a timeout is set.
+    pub fn with_timeout<O>(mut self, timeout: O) -> Self
+    where
+        O: Into<Option<Timeout>>,
+    {
+        self.timeout = timeout.into();
+        self
+    }
+
+    /// Makes the Escape key cancel reading a password interactively.
+    ///
+    /// Ctrl-C, and Ctrl-D when nothing has been typed, always cancel reading. A cancelled read
+    /// returns `Error::Cancelled`.
+    pub fn with_cancel_on_escape(mut self, cancel_on_escape: bool) -> Self {
+        self.cancel_on_escape = cancel_on_escape;
+        self
+    }
+
+    /// Sets a key which shows the password in plain text while it is typed interactively, e.g.
+    /// `Key::Tab` or Ctrl-R (`Key::Char('\u{12}')`).
+    ///
+    /// Pressing the key again masks the password, and it is masked before reading ends. Set to
+    /// `None` (the default) to never show the password.
+    pub fn with_reveal_key<K>(mut self, key: K) -> Self
+    where
+        K: Into<Option<Key>>,
+    {
+        self.reveal_key = key.into();
+        self
+    }
+
+    /// Keeps the line terminator when reading from redirected stdin.
+    ///
+    /// By default, a trailing `\n` or `\r\n` is removed from the line read in non-interactive
+    /// mode. When enabled, the input is returned exactly as read.
+    pub fn with_preserved_line_ending(mut self, preserve_line_ending: bool) -> Self {
+        self.preserve_line_ending = preserve_line_ending;
+        self
+    }
+
+    /// Sets where prompts, messages and echoed symbols are written to.
+    ///
+    /// Defaults to `PromptSink::Stderr`. When the controlling terminal is used instead of
+    /// redirected stdin (see `with_tty`), everything is written to the controlling terminal
+    /// regardless of this setting.
+    pub fn with_prompt_sink(mut self, prompt_sink: PromptSink) -> Self {
+        self.prompt_sink = prompt_sink;
+        self
+    }
+
+    /// Enables reading from the controlling terminal (`/dev/tty`) when stdin is redirected.
+    ///
+    /// When enabled and stdin is not a terminal, the prompt is written to and the password is
+    /// read from the controlling terminal, leaving the redirected stdin untouched (like `sudo` or
+    /// `ssh` do). If there is no controlling terminal either, reading fails with
+    /// `Error::NoTerminal` instead of falling back to stdin.
+    ///
+    /// `is_interactive` also returns `true` when the controlling terminal is used.
+    ///
+    /// The controlling terminal is only available on Unix-like systems.
+    pub fn with_tty(mut self, use_tty: bool) -> Self {
+        self.use_tty = use_tty;
+        self
+    }
+
+    /// Opens the controlling terminal if it should be used instead of the redirected stdin.
+    fn tty(&self) -> Result<Option<T>> {
+        if !self.use_tty || self.terminal.is_terminal() {
+            return Ok(None);
+        }
+        self.terminal
+            .open_tty()
+            .map(Some)
+            .map_err(Error::NoTerminal)
+    }
+
+    /// Opens the output for prompts, messages and echoed symbols.
+    fn output(&self) -> Result<Output<T>> {
+        match self.tty()? {
+            Some(tty) => Ok(Output::Terminal(tty)),
+            None => Ok(self.prompt_sink.open(&self.terminal)?),
+        }
+    }
+
+    /// Writes a prompt or a message for the user.
+    fn print(&self, text: &str) -> Result<()> {
+        let mut output = self.output()?;
+        write!(output, "{text}")?;
+        Ok(output.flush()?)
+    }
+
+    /// Reads a password from the first available source, or asks for it.
+    fn read_validated(&self, prompt: Option<&str>) -> Result<SecretString> {
+        match self.input(prompt)? {
+            Input::Taken(password) => self.checked(password),
+            Input::Ask(yapp) => yapp.ask_validated(prompt),
+        }
+    }
+
+    /// Takes a password from the first available non-interactive source, or returns the reader
+    /// to ask the user with.
+    fn input(&self, prompt: Option<&str>) -> Result<Input<T>> {
+        for source in &self.sources {
+            let use_tty = match source {
+                Source::Tty if self.terminal.is_terminal() || self.terminal.open_tty().is_ok() => {
+                    true
+                }
+                Source::Tty => continue,
+                Source::Stdin => false,
+                _ => match source.read(prompt, self.max_length, self.env_var, |warning| {
+                    self.print(warning)
+                })? {
+                    Some(password) => return Ok(Input::Taken(password)),
+                    None => continue,
+                },
+            };
+            return Ok(Input::Ask(Yapp {
+                use_tty,
+                sources: Vec::new(),
+                ..self.clone()
+            }));
+        }
+        Ok(Input::Ask(self.clone()))
+    }
+
+    /// Returns a password which was not typed, if it passes validation.
+    fn checked(&self, password: SecretString) -> Result<SecretString> {
+        if let Some(validator) = &self.validator {
+            validator
+                .validate(password.expose_secret())
+                .map_err(Error::Validation)?;
+        }
+        self.check_strength(&password)?;
+        Ok(password)
+    }
+
+    /// Asks for a password, asking again in interactive mode until it passes validation.
+    fn ask_validated(&self, prompt: Option<&str>) -> Result<SecretString> {
+        let mut attempt = 1;
+        loop {
+            if let Some(prompt) = prompt {
+                self.print(prompt)?;
+            }
+            let password = self.read(true)?;
+            let Some(validator) = &self.validator else {
+                return Ok(password);
+            };
+            let Err(message) = validator.validate(password.expose_secret()) else {
+                return Ok(password);
+            };
+            if !self.is_interactive() || attempt >= self.validation_attempts {
+                return Err(Error::Validation(message));
+            }
+            self.print(&format!("{message}\n"))?;
+            attempt += 1;
+        }
+    }
+
+    /// Reads a password interactively or non-interactively. The strength of a `new_password`
+    /// is shown and checked, the strength of its confirmation is not.
+    fn read(&self, new_password: bool) -> Result<SecretString> {
+        let term = if self.terminal.is_terminal() {
+            self.terminal.clone()
+        } else if let Some(tty) = self.tty()? {
+            tty
+        } else {
+            let password = self.read_non_interactive()?;
+            if new_password {
+                self.check_strength(&password)?;
+            }
+            return Ok(password);
+        };
+        self.read_interactive(&term, self.output()?, new_password)
+    }
+
+    /// Returns an error if the password is weaker than the minimum strength.
+    fn check_strength(&self, password: &SecretString) -> Result<()> {
+        match self.min_strength {
+            Some(min_strength) if !is_strong_enough(password.expose_secret(), min_strength) => {
+                Err(Error::Validation(format!(
+                    "password is {}, it must be at least {min_strength}",
+                    Strength::estimate(password.expose_secret())
+                )))
+            }
+            _ => Ok(()),
+        }
     }
 
     /// Reads a password from a non-interactive terminal.
-    fn read_non_interactive(&self) -> io::Result<String> {
-        let mut input = String::new();
-        let stdin = stdin();
-        stdin.read_line(&mut input)?;
-        if let Some(s) = self.echo_symbol {
-            writeln!(stdout(), "{}", format!("{s}").repeat(input.len()))?;
+    fn read_non_interactive(&self) -> Result<SecretString> {
+        // Read byte by byte, so that nothing after the line is taken from stdin.
+        let stdin = TimedReader::new(
+            &self.terminal,
+            Waiter::new(self.timeout, self.cancelled.clone()),
+        );
+        let mut input =
+            secret::read_line(&mut SecretReader::with_capacity(1, stdin), self.max_length)?;
+        if !self.preserve_line_ending {
+            input.trim_line_ending();
+        }
+        if self.mask.is_visible() {
+            let password = input.expose_secret();
+            let (echo, _) =
+                Masking::new(&self.mask).echo(password, password.chars().count(), false);
+            writeln!(self.output()?, "{}", *echo)?;
         }
         Ok(input)
     }
 
     /// Reads a password from an interactive terminal.
-    fn read_interactive(&self) -> io::Result<String> {
-        let mut term = Term::stdout();
-        let mut input = String::new();
+    fn read_interactive(
+        &self,
+        term: &T,
+        output: Output<T>,
+        new_password: bool,
+    ) -> Result<SecretString> {
+        let min_strength = self.min_strength.filter(|_| new_password);
+        let mut editor = LineEditor::new(self.cancel_on_escape, self.max_length);
+        let mut echo = Echo {
+            output,
+            screen: Screen::default(),
+            masking: Masking::new(&self.mask),
+            strength_meter: new_password && self.strength_meter,
+            revealed: false,
+            show_last_until: None,
+        };
+        let mut waiter = Waiter::new(self.timeout, self.cancelled.clone());
         loop {
-            let key = term.read_key()?;
-            match key {
-                Key::Char(c) => {
-                    input.push(c);
-                    if let Some(s) = self.echo_symbol {
-                        write!(term, "{s}")?;
+            let key = match &mut waiter {
+                Some(waiter) => {
+                    let mut key = None;
+                    let waited = waiter.wait(|timeout| {
+                        key = echo.read_key(term, Some(timeout), &editor)?;
+                        Ok(key.is_some())
+                    });
+                    if let Err(e) = waited {
+                        echo.mask(&editor)?;
+                        writeln!(echo.output)?;
+                        return Err(e);
                     }
+                    waiter.reset();
+                    key.expect("a key has been read")
                 }
-                Key::Backspace if !input.is_empty() => {
-                    input.pop();
-                    term.clear_chars(1)?;
+                None => echo
+                    .read_key(term, None, &editor)?
+                    .expect("reading a key without a timeout returns it"),
+            };
+            if self.reveal_key.as_ref() == Some(&key) {
+                echo.revealed = !echo.revealed;
+                echo.show_last_until = None;
+                echo.draw(&editor)?;
+                continue;
+            }
+            let len = editor.len();
+            match editor.handle_key(key) {
+                Action::Continue => {
+                    let typed = matches!(self.mask, Mask::ShowLast(_)) && editor.len() > len;
+                    echo.show_last_until = typed.then(|| Instant::now() + mask::SHOW_LAST_DURATION);
+                    echo.draw(&editor)?;
                 }
-                Key::Enter => {
-                    term.write_line("")?;
-                    break;
+                Action::Rejected => self.ring_bell(&mut echo.output)?,
+                Action::Submit
+                    if min_strength
+                        .is_some_and(|min| !is_strong_enough(editor.expose_secret(), min)) =>
+                {
+                    self.ring_bell(&mut echo.output)?
+                }
+                Action::Submit => break,
+                Action::Cancel => {
+                    echo.mask(&editor)?;
+                    writeln!(echo.output)?;
+                    return Err(Error::Cancelled);
                 }
-                _ => {}
             }
         }
-        Ok(input)
+        echo.mask(&editor)?;
+        writeln!(echo.output)?;
+        Ok(editor.into_secret())
+    }
+
+    fn ring_bell(&self, output: &mut dyn Write) -> Result<()> {
+        if self.bell {
+            write!(output, "{BELL}")?;
+            output.flush()?;
+        }
+        Ok(())
     }
 }
+
+/// What is shown while a password is typed interactively.
+struct Echo<'a, T> {
+    output: Output<T>,
+    screen: Screen,
+    masking: Masking<'a>,
+    strength_meter: bool,
+    /// Set when the password is shown in plain text (see `Yapp::with_reveal_key`).
+    revealed: bool,
+    /// Until when the last typed character is shown (see `Mask::ShowLast`).
+    show_last_until: Option<Instant>,
+}
+
+impl<T: Terminal> Echo<'_, T> {
+    /// Reads a key, waiting at most `timeout` for it. The last typed character is hidden when
+    /// it has been shown long enough, even if no key is pressed.
+    fn read_key(
+        &mut self,
+        term: &T,
+        timeout: Option<Duration>,
+        editor: &LineEditor,
+    ) -> io::Result<Option<Key>> {
+        loop {
+            let Some(until) = self.show_last_until else {
+                return term.read_key(timeout);
+            };
+            let remaining = until.saturating_duration_since(Instant::now());
+            match term.read_key(Some(timeout.map_or(remaining, |t| t.min(remaining)))) {
+                // Keep showing the character until the next key press.
+                Err(e) if e.kind() == io::ErrorKind::Unsupported && timeout.is_none() => {
+                    return term.read_key(None);
+                }
+                Ok(None) => {
+                    if Instant::now() >= until {
+                        self.show_last_until = None;
+                        self.draw(editor)?;
+                    }
+                    if timeout.is_some() {
+                        return Ok(None);
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Redraws the echo of the password being typed.
+    fn draw(&mut self, editor: &LineEditor) -> io::Result<()> {
+        use std::fmt::Write as _;
+
+        let password = editor.expose_secret();
+        // Reserved up front, including the strength meter, so that no copy of the password is
+        // left behind when growing.
+        let mut echo = Zeroizing::new(String::with_capacity(
+            Masking::capacity(password) + STRENGTH_METER_CAPACITY,
+        ));
+        let cursor = if self.revealed {
+            echo.push_str(password);
+            editor.cursor()
+        } else {
+            self.masking.echo_into(
+                &mut echo,
+                password,
+                editor.cursor(),
+                self.show_last_until.is_some(),
+            )
+        };
+        if self.strength_meter && !editor.is_empty() {
+            write!(echo, " [{}]", Strength::estimate(password))
+                .map_err(|_| io::Error::from(io::ErrorKind::Other))?;
+        }
+        self.screen.update(&mut self.output, &echo, cursor)
+    }
+
+    /// Leaves only the masked password on the screen, hiding the strength meter, a revealed
+    /// password and the last typed character.
+    fn mask(&mut self, editor: &LineEditor) -> io::Result<()> {
+        if !self.strength_meter && !self.revealed && self.show_last_until.is_none() {
+            return Ok(());
+        }
+        self.strength_meter = false;
+        self.revealed = false;
+        self.show_last_until = None;
+        self.draw(editor)
+    }
+}
+
+fn is_strong_enough(password: &str, min_strength: Strength) -> bool {
+    Strength::estimate(password) >= min_strength
+}
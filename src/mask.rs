@@ -0,0 +1,140 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+/// How long `Mask::ShowLast` shows the last typed character.
+pub(crate) const SHOW_LAST_DURATION: Duration = Duration::from_secs(1);
+
+/// How a password is echoed while it is typed.
+///
+/// Set with `Yapp::with_mask`. `Symbol` shows how long the password is, the others hide it from
+/// shoulder-surfers and screen recordings, except for `Counter` and `ShowLast`, which help
+/// typing long passwords instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Mask {
+    /// Nothing is echoed (the default).
+    Hidden,
+    /// The symbol is echoed for every character typed, e.g. `*`.
+    Symbol(char),
+    /// The same text, e.g. `"********"`, is echoed whenever anything has been typed, whatever
+    /// the length of the password.
+    Fixed(String),
+    /// A random number of symbols, between 1 and `max`, is echoed for every character typed.
+    /// The numbers are picked anew for every password read.
+    Decoy {
+        /// The symbol echoed.
+        symbol: char,
+        /// The most symbols echoed for a character.
+        max: usize,
+    },
+    /// The number of characters typed is echoed, e.g. `[12 chars]`.
+    Counter,
+    /// The symbol is echoed for every character typed, except for the last one, which is shown
+    /// for a second, or until the next key is pressed. Where the terminal can't wait for keys
+    /// with a timeout (see `Terminal`), it is shown until the next key is pressed.
+    ShowLast(char),
+}
+
+impl Mask {
+    /// Checks if anything is echoed.
+    pub(crate) fn is_visible(&self) -> bool {
+        *self != Mask::Hidden
+    }
+}
+
+impl From<Option<char>> for Mask {
+    fn from(symbol: Option<char>) -> Self {
+        symbol.map_or(Mask::Hidden, Mask::Symbol)
+    }
+}
+
+/// Echoes a password according to a `Mask`, the same way for every key press of a single read.
+pub(crate) struct Masking<'a> {
+    mask: &'a Mask,
+    /// Picks the number of `Mask::Decoy` symbols for every character.
+    decoys: RandomState,
+}
+
+impl<'a> Masking<'a> {
+    pub(crate) fn new(mask: &'a Mask) -> Self {
+        Masking {
+            mask,
+            decoys: RandomState::new(),
+        }
+    }
+
+    /// Returns the text echoed for `password`, and the position of the cursor in it, when the
+    /// cursor is after `cursor` characters of the password. With `show_last`, the character
+    /// before the cursor is shown by `Mask::ShowLast`.
+    pub(crate) fn echo(
+        &self,
+        password: &str,
+        cursor: usize,
+        show_last: bool,
+    ) -> (Zeroizing<String>, usize) {
+        let mut echo = Zeroizing::new(String::with_capacity(Self::capacity(password)));
+        let cursor = self.echo_into(&mut echo, password, cursor, show_last);
+        (echo, cursor)
+    }
+
+    /// The bytes to reserve for the echo of `password`, so that no copy of a shown character is
+    /// left behind when growing the buffer.
+    pub(crate) fn capacity(password: &str) -> usize {
+        password.len() * 4
+    }
+
+    /// Appends the text echoed for `password` to `echo`, and returns the position of the cursor
+    /// in the appended text, like `echo`. At least `capacity` bytes should be reserved in `echo`.
+    pub(crate) fn echo_into(
+        &self,
+        echo: &mut String,
+        password: &str,
+        cursor: usize,
+        show_last: bool,
+    ) -> usize {
+        let len = password.chars().count();
+        match self.mask {
+            Mask::Hidden => 0,
+            Mask::Symbol(symbol) => {
+                echo.extend(std::iter::repeat(*symbol).take(len));
+                cursor
+            }
+            Mask::Fixed(_) | Mask::Counter if len == 0 => 0,
+            Mask::Fixed(text) => {
+                echo.push_str(text);
+                text.chars().count()
+            }
+            Mask::Counter => {
+                let counter = format!("[{len} {}]", if len == 1 { "char" } else { "chars" });
+                echo.push_str(&counter);
+                counter.chars().count()
+            }
+            Mask::Decoy { symbol, max } => {
+                let mut before = 0;
+                for index in 0..len {
+                    let count = self.decoy_count(index, *max);
+                    echo.extend(std::iter::repeat(*symbol).take(count));
+                    if index < cursor {
+                        before += count;
+                    }
+                }
+                before
+            }
+            Mask::ShowLast(symbol) => {
+                let last = cursor.checked_sub(1).filter(|_| show_last);
+                for (index, c) in password.chars().enumerate() {
+                    echo.push(if Some(index) == last { c } else { *symbol });
+                }
+                cursor
+            }
+        }
+    }
+
+    fn decoy_count(&self, index: usize, max: usize) -> usize {
+        let mut hasher = self.decoys.build_hasher();
+        hasher.write_usize(index);
+        1 + (hasher.finish() % max.max(1) as u64) as usize
+    }
+}
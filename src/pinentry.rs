@@ -0,0 +1,257 @@
+use crate::assuan::{self, AssuanError};
+use crate::secret::{self, SecretReader};
+use crate::{Error, PasswordReader, Result, SecretString};
+use std::env;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+/// A `PasswordReader` asking for passwords with a `pinentry` program, like GnuPG does.
+///
+/// A new pinentry process is started for every password, and driven over the Assuan protocol.
+/// Pinentry programs draw their own masked input, so the echo symbol is ignored.
+///
+/// ```rust,no_run
+/// use yapp::{PasswordReader, Pinentry};
+///
+/// let mut pinentry = Pinentry::new().with_description("Unlock the signing key");
+/// let password = pinentry.read_password_with_prompt("Passphrase:").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pinentry {
+    program: PathBuf,
+    title: Option<String>,
+    description: Option<String>,
+}
+
+impl Default for Pinentry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pinentry {
+    /// Creates a reader running the `pinentry` program found in `PATH`.
+    pub fn new() -> Self {
+        Pinentry {
+            program: PathBuf::from("pinentry"),
+            title: None,
+            description: None,
+        }
+    }
+
+    /// Sets the pinentry program to run, e.g. `pinentry-curses`.
+    pub fn with_program<P: Into<PathBuf>>(mut self, program: P) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    /// Sets the title of the pinentry window.
+    pub fn with_title<T: Into<String>>(mut self, title: T) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the text shown above the input, explaining what the password is needed for.
+    pub fn with_description<D: Into<String>>(mut self, description: D) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Asks the user to confirm `message` (shown instead of the description).
+    ///
+    /// Returns `false` if the user declined, and `Error::Cancelled` if the dialog was closed.
+    pub fn confirm(&mut self, message: &str) -> Result<bool> {
+        let mut session = self.start()?;
+        session.set("SETDESC", message)?;
+        match session.command("CONFIRM")? {
+            Ok(_) => Ok(true),
+            Err(e) if e.code() == assuan::GPG_ERR_NOT_CONFIRMED => Ok(false),
+            Err(e) => Err(into_error(e)),
+        }
+    }
+
+    /// Starts pinentry and sends the settings to it.
+    fn start(&self) -> Result<Session> {
+        let mut session = Session::start(&self.program)?;
+        // Curses pinentry programs need to be told which terminal to use, like gpg-agent does.
+        // They may not know the options, so failures are ignored.
+        for (option, variable) in [("ttyname", "GPG_TTY"), ("ttytype", "TERM")] {
+            if let Ok(value) = env::var(variable) {
+                let _ = session.command(&format!("OPTION {option}={}", assuan::encode(&value)))?;
+            }
+        }
+        if let Some(title) = &self.title {
+            session.set("SETTITLE", title)?;
+        }
+        if let Some(description) = &self.description {
+            session.set("SETDESC", description)?;
+        }
+        Ok(session)
+    }
+
+    fn get_pin(&self, prompt: Option<&str>) -> Result<SecretString> {
+        let mut session = self.start()?;
+        if let Some(prompt) = prompt {
+            session.set("SETPROMPT", prompt.trim_end())?;
+        }
+        session.get_pin()
+    }
+}
+
+impl PasswordReader for Pinentry {
+    fn read_password(&mut self) -> Result<String> {
+        self.get_pin(None).map(SecretString::into_string)
+    }
+
+    fn read_password_with_prompt(&mut self, prompt: &str) -> Result<String> {
+        self.get_pin(Some(prompt)).map(SecretString::into_string)
+    }
+
+    fn read_secret(&mut self) -> Result<SecretString> {
+        self.get_pin(None)
+    }
+
+    fn read_secret_with_prompt(&mut self, prompt: &str) -> Result<SecretString> {
+        self.get_pin(Some(prompt))
+    }
+
+    fn read_password_with_confirmation(
+        &mut self,
+        prompt: &str,
+        confirm_prompt: &str,
+        mismatch_message: &str,
+        max_attempts: usize,
+    ) -> Result<String> {
+        self.read_secret_with_confirmation(prompt, confirm_prompt, mismatch_message, max_attempts)
+            .map(SecretString::into_string)
+    }
+
+    /// Asks for both entries within a single pinentry session. The mismatch message is shown as
+    /// an error in the dialog.
+    fn read_secret_with_confirmation(
+        &mut self,
+        prompt: &str,
+        confirm_prompt: &str,
+        mismatch_message: &str,
+        max_attempts: usize,
+    ) -> Result<SecretString> {
+        let mut session = self.start()?;
+        let attempts = max_attempts.max(1);
+        for attempt in 0..attempts {
+            if attempt > 0 {
+                session.set("SETERROR", mismatch_message)?;
+            }
+            session.set("SETPROMPT", prompt.trim_end())?;
+            let password = session.get_pin()?;
+            session.set("SETPROMPT", confirm_prompt.trim_end())?;
+            let confirmation = session.get_pin()?;
+            if password == confirmation {
+                return Ok(password);
+            }
+        }
+        Err(Error::Mismatch { attempts })
+    }
+
+    fn with_echo_symbol<C>(self, _c: C) -> Self
+    where
+        C: 'static + Into<Option<char>>,
+    {
+        self
+    }
+}
+
+/// A running pinentry process. It is told to quit when dropped.
+struct Session {
+    child: Child,
+    input: ChildStdin,
+    /// The responses, including the PIN, read through a buffer which is wiped when dropped.
+    output: SecretReader<ChildStdout>,
+}
+
+impl Session {
+    fn start(program: &Path) -> Result<Self> {
+        let mut child = Command::new(program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let input = child.stdin.take().expect("stdin of pinentry is piped");
+        let output = SecretReader::new(child.stdout.take().expect("stdout of pinentry is piped"));
+        let mut session = Session {
+            child,
+            input,
+            output,
+        };
+        // The greeting.
+        session.response()?.map_err(into_error)?;
+        Ok(session)
+    }
+
+    /// Sends a command setting a text, e.g. `SETPROMPT`.
+    fn set(&mut self, command: &str, text: &str) -> Result<()> {
+        self.command(&format!("{command} {}", assuan::encode(text)))?
+            .map_err(into_error)?;
+        Ok(())
+    }
+
+    fn get_pin(&mut self) -> Result<SecretString> {
+        self.command("GETPIN")?.map_err(into_error)
+    }
+
+    /// Sends a command and returns the data sent back, or the `ERR` response.
+    fn command(&mut self, command: &str) -> Result<Result<SecretString, AssuanError>> {
+        writeln!(self.input, "{command}")?;
+        self.input.flush()?;
+        self.response()
+    }
+
+    /// Reads response lines up to `OK` or `ERR`.
+    fn response(&mut self) -> Result<Result<SecretString, AssuanError>> {
+        let mut data = Vec::new();
+        loop {
+            let line = secret::read_line(&mut self.output, None)?;
+            if line.is_empty() {
+                return Err(Error::Io(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "pinentry quit unexpectedly",
+                )));
+            }
+            let text = line.expose_secret().trim_end_matches(['\r', '\n']);
+            let (keyword, args) = text.split_once(' ').unwrap_or((text, ""));
+            match keyword {
+                "OK" => {
+                    let data: Vec<&str> = data.iter().map(payload).collect();
+                    return Ok(Ok(assuan::decode(&data)?));
+                }
+                "ERR" => return Ok(Err(AssuanError::parse(args))),
+                "D" => data.push(line),
+                // Status lines and comments.
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let _ = writeln!(self.input, "BYE").and_then(|_| self.input.flush());
+        let _ = self.child.wait();
+    }
+}
+
+/// The data of a `D` response line.
+fn payload(line: &SecretString) -> &str {
+    let line = line.expose_secret().trim_end_matches(['\r', '\n']);
+    line.get(2..).unwrap_or_default()
+}
+
+fn into_error(e: AssuanError) -> Error {
+    match e.code() {
+        assuan::GPG_ERR_CANCELED => Error::Cancelled,
+        assuan::GPG_ERR_TIMEOUT => Error::TimedOut,
+        _ => Error::Io(io::Error::new(
+            io::ErrorKind::Other,
+            format!("pinentry failed: {}", e.message),
+        )),
+    }
+}
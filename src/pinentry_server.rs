@@ -0,0 +1,246 @@
+use crate::assuan;
+use crate::sys::{attach_tty, detach_stdio};
+use crate::{
+    DefaultTerminal, Error, Mask, PasswordReader, Result, SecretString, Terminal, Timeout, Yapp,
+};
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+/// Default path of the terminal to ask on, when not told otherwise.
+const DEFAULT_TTY: &str = "/dev/tty";
+/// Default prompt, the same as the one of GnuPG's pinentry programs.
+const DEFAULT_PROMPT: &str = "PIN:";
+/// Default message shown when the repeated PIN does not match.
+const DEFAULT_REPEAT_ERROR: &str = "Does not match - try again";
+
+/// A pinentry program built on `Yapp`, answering e.g. `gpg-agent` over the Assuan protocol.
+///
+/// PINs are asked for with the given `Yapp`, on the terminal named by the `ttyname` option
+/// (`/dev/tty` by default). The title, description and error set by the client are printed
+/// above the prompt. `CONFIRM` and `MESSAGE` ask the user to press Enter, or to cancel.
+///
+/// Available with the `pinentry-server` feature, which also builds the `yapp-pinentry` binary:
+///
+/// ```text
+/// # ~/.gnupg/gpg-agent.conf
+/// pinentry-program /path/to/yapp-pinentry
+/// ```
+#[derive(Debug, Clone)]
+pub struct PinentryServer<T = DefaultTerminal> {
+    yapp: Yapp<T>,
+    settings: Settings,
+    /// Points stdin and stdout at the terminal named by the client.
+    pub(crate) attach_tty: fn(&str) -> io::Result<()>,
+}
+
+/// What the client has set up for the next dialog.
+#[derive(Debug, Clone, Default)]
+struct Settings {
+    tty_name: Option<String>,
+    title: Option<String>,
+    description: Option<String>,
+    prompt: Option<String>,
+    error: Option<String>,
+    repeat: Option<String>,
+    repeat_error: Option<String>,
+    timeout: Option<Duration>,
+}
+
+impl<T: Terminal> PinentryServer<T> {
+    /// Creates a server asking for PINs with `yapp`.
+    pub fn new(yapp: Yapp<T>) -> Self {
+        PinentryServer {
+            yapp,
+            settings: Settings::default(),
+            attach_tty,
+        }
+    }
+
+    /// Serves the client on stdin and stdout, until it says `BYE` or closes stdin.
+    ///
+    /// The protocol is moved off stdin and stdout, which are pointed at the terminal when
+    /// asking for a PIN, so that keys can be read from it.
+    pub fn serve_stdio(&mut self) -> Result<()> {
+        let (input, output) = detach_stdio()?;
+        self.serve(std::io::BufReader::new(input), output)
+    }
+
+    /// Serves the client, until it says `BYE` or closes the input.
+    pub fn serve<R: BufRead, W: Write>(&mut self, mut input: R, mut output: W) -> Result<()> {
+        writeln!(output, "OK Pleased to meet you, yapp pinentry here")?;
+        output.flush()?;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if input.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+            let bye = command.eq_ignore_ascii_case("BYE");
+            self.handle(&command.to_ascii_uppercase(), args, &mut output)?;
+            output.flush()?;
+            if bye {
+                return Ok(());
+            }
+        }
+    }
+
+    fn handle<W: Write>(&mut self, command: &str, args: &str, output: &mut W) -> Result<()> {
+        let settings = &mut self.settings;
+        match command {
+            "SETTITLE" => settings.title = Some(decode(args)?),
+            "SETDESC" => settings.description = Some(decode(args)?),
+            "SETPROMPT" => settings.prompt = Some(decode(args)?),
+            "SETERROR" => settings.error = Some(decode(args)?),
+            "SETREPEAT" => settings.repeat = Some(decode(args)?),
+            "SETREPEATERROR" => settings.repeat_error = Some(decode(args)?),
+            "SETTIMEOUT" => {
+                settings.timeout = args
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|&seconds| seconds > 0)
+                    .map(Duration::from_secs)
+            }
+            "OPTION" => {
+                let option = decode(args)?;
+                let (name, value) = option.split_once(['=', ' ']).unwrap_or((&option, ""));
+                if name == "ttyname" {
+                    settings.tty_name = Some(value.to_owned());
+                }
+            }
+            "GETINFO" => {
+                let info = match args.trim() {
+                    "version" => env!("CARGO_PKG_VERSION").to_owned(),
+                    "pid" => std::process::id().to_string(),
+                    "flavor" => String::from("yapp"),
+                    _ => {
+                        return reply_error(
+                            output,
+                            assuan::GPG_ERR_ASS_PARAMETER,
+                            "Invalid parameter",
+                        )
+                    }
+                };
+                writeln!(output, "D {}", assuan::encode(&info))?;
+            }
+            "GETPIN" => {
+                let pin = match self.get_pin() {
+                    Ok(pin) => pin,
+                    Err(e) => return reply_failure(output, e, assuan::GPG_ERR_CANCELED),
+                };
+                if self.settings.repeat.is_some() {
+                    writeln!(output, "S PIN_REPEATED")?;
+                }
+                if !pin.is_empty() {
+                    writeln!(
+                        output,
+                        "D {}",
+                        assuan::encode_secret(pin.expose_secret()).as_str()
+                    )?;
+                }
+            }
+            "CONFIRM" | "MESSAGE" => {
+                if let Err(e) = self.confirm() {
+                    return reply_failure(output, e, assuan::GPG_ERR_NOT_CONFIRMED);
+                }
+            }
+            "RESET" => {
+                *settings = Settings {
+                    tty_name: settings.tty_name.take(),
+                    ..Settings::default()
+                }
+            }
+            // Button labels, quality bar and the like are not shown.
+            "SETOK" | "SETCANCEL" | "SETNOTOK" | "SETQUALITYBAR" | "SETQUALITYBAR_TT"
+            | "SETGENPIN" | "SETGENPIN_TT" | "SETKEYINFO" | "NOP" | "BYE" => {}
+            _ => {
+                return reply_error(
+                    output,
+                    assuan::GPG_ERR_ASS_UNKNOWN_CMD,
+                    "Unknown IPC command",
+                )
+            }
+        }
+        writeln!(output, "OK")?;
+        Ok(())
+    }
+
+    fn get_pin(&mut self) -> Result<SecretString> {
+        let mut yapp = self.dialog()?;
+        let prompt = format!(
+            "{} ",
+            self.settings.prompt.as_deref().unwrap_or(DEFAULT_PROMPT)
+        );
+        match &self.settings.repeat {
+            Some(repeat) => yapp.read_secret_with_confirmation(
+                &prompt,
+                &format!("{repeat} "),
+                self.settings
+                    .repeat_error
+                    .as_deref()
+                    .unwrap_or(DEFAULT_REPEAT_ERROR),
+                usize::MAX,
+            ),
+            None => yapp.read_secret_with_prompt(&prompt),
+        }
+    }
+
+    fn confirm(&mut self) -> Result<()> {
+        // Whatever is typed is thrown away, so it is neither echoed nor checked.
+        let mut yapp = Yapp {
+            mask: Mask::Hidden,
+            validator: None,
+            min_strength: None,
+            sources: Vec::new(),
+            ..self.dialog()?
+        };
+        yapp.read_secret_with_prompt("Press Enter to continue, or Ctrl-C to cancel ")?;
+        Ok(())
+    }
+
+    /// Attaches the terminal and prints the texts shown above the input. Returns the reader to
+    /// ask with.
+    fn dialog(&mut self) -> Result<Yapp<T>> {
+        (self.attach_tty)(self.settings.tty_name.as_deref().unwrap_or(DEFAULT_TTY))
+            .map_err(Error::NoTerminal)?;
+        let yapp = match self.settings.timeout {
+            Some(timeout) => self.yapp.clone().with_timeout(Timeout::Total(timeout)),
+            None => self.yapp.clone(),
+        };
+        // An error is shown only once, like a pinentry dialog does.
+        let error = self.settings.error.take();
+        let texts = [&self.settings.title, &self.settings.description, &error];
+        for text in texts.into_iter().flatten() {
+            yapp.print(&format!("{text}\n"))?;
+        }
+        Ok(yapp)
+    }
+}
+
+fn decode(args: &str) -> Result<String> {
+    Ok(assuan::decode(&[args])?.into_string())
+}
+
+/// Replies to a failed dialog, with `cancelled` as the error code when the user cancelled it.
+fn reply_failure<W: Write>(output: &mut W, e: Error, cancelled: u32) -> Result<()> {
+    match e {
+        Error::Cancelled => reply_error(output, cancelled, "Operation cancelled"),
+        Error::TimedOut => reply_error(output, assuan::GPG_ERR_TIMEOUT, "Timeout"),
+        e => reply_error(output, assuan::GPG_ERR_GENERAL, &e.to_string()),
+    }
+}
+
+fn reply_error<W: Write>(output: &mut W, code: u32, message: &str) -> Result<()> {
+    writeln!(
+        output,
+        "ERR {} {} <Pinentry>",
+        assuan::GPG_ERR_SOURCE_PINENTRY | code,
+        assuan::encode(message)
+    )?;
+    Ok(())
+}
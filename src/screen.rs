@@ -0,0 +1,91 @@
+use crate::sink::Output;
+use crate::Terminal;
+use std::io::{self, Write};
+use unicode_width::UnicodeWidthChar;
+use zeroize::Zeroizing;
+
+/// Moves the cursor one column back.
+const BACK: char = '\x08';
+
+/// Keeps track of the text echoed after the prompt, and redraws it when it changes.
+///
+/// Only the part of the line which differs from what is already shown is rewritten. The cursor is
+/// moved with backspace characters, which are understood by all terminals, one per column of the
+/// characters it moves over (e.g. two for wide CJK characters or emoji). Symbols deleted from the
+/// end of the line are cleared with `Terminal::clear_chars`. The text is wiped from memory, as it
+/// is the password itself when revealed.
+#[derive(Default)]
+pub(crate) struct Screen {
+    shown: Zeroizing<Vec<char>>,
+    /// Number of characters of `shown` before the cursor.
+    column: usize,
+}
+
+impl Screen {
+    /// Redraws the echoed `text` and places the cursor after `cursor` characters of it.
+    pub(crate) fn update<T: Terminal>(
+        &mut self,
+        output: &mut Output<T>,
+        text: &str,
+        cursor: usize,
+    ) -> io::Result<()> {
+        // Buffers are reserved up front, so that no copy of the text is left behind when growing.
+        let mut chars = Zeroizing::new(Vec::with_capacity(text.chars().count()));
+        chars.extend(text.chars());
+        let text = chars;
+        let common = self
+            .shown
+            .iter()
+            .zip(text.iter())
+            .take_while(|(shown, new)| shown == new)
+            .count();
+        if common == text.len() && cursor == common && self.column == self.shown.len() {
+            // Only the end of the line was deleted, which is left to the terminal to clear.
+            if common < self.shown.len() {
+                output.clear_chars(width(&self.shown[common..]))?;
+            }
+            self.shown = text;
+            self.column = cursor;
+            return Ok(());
+        }
+        // The cursor is moved back over the changed characters, or forward by rewriting the
+        // unchanged ones.
+        let (back, forward) = if self.column > common {
+            (width(&self.shown[common..self.column]), &[][..])
+        } else {
+            (0, &self.shown[self.column..common])
+        };
+        let stale = width(&self.shown).saturating_sub(width(&text));
+        let end = width(&text[cursor..]);
+        let mut buf = Zeroizing::new(String::with_capacity(
+            back + (forward.len() + text.len() - common) * 4 + stale * 2 + end,
+        ));
+        buf.extend(std::iter::repeat(BACK).take(back));
+        buf.extend(forward);
+        buf.extend(&text[common..]);
+        buf.extend(std::iter::repeat(' ').take(stale));
+        buf.extend(std::iter::repeat(BACK).take(stale + end));
+        if !buf.is_empty() {
+            output.write_all(buf.as_bytes())?;
+            output.flush()?;
+        }
+        self.shown = text;
+        self.column = cursor;
+        Ok(())
+    }
+}
+
+/// The text clearing `count` columns before the cursor: they are overwritten with spaces and the
+/// cursor is moved back.
+pub(crate) fn clearing(count: usize) -> String {
+    let mut text = String::with_capacity(count * 3);
+    text.extend(std::iter::repeat(BACK).take(count));
+    text.extend(std::iter::repeat(' ').take(count));
+    text.extend(std::iter::repeat(BACK).take(count));
+    text
+}
+
+/// Number of terminal columns taken by the characters.
+fn width(chars: &[char]) -> usize {
+    chars.iter().filter_map(|c| c.width()).sum()
+}
@@ -0,0 +1,246 @@
+use crate::Error;
+use std::fmt;
+use std::io::{self, BufRead, Read};
+use std::ops::Range;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Number of bytes reserved up front, so that typical passwords never trigger a reallocation.
+const INITIAL_CAPACITY: usize = 128;
+
+/// A password which wipes its memory when dropped.
+///
+/// The buffer is pre-allocated, and when it needs to grow the old allocation is wiped before
+/// being released, so no copies of the password are left behind in freed memory. `Debug` and
+/// `Display` never reveal the content; use `expose_secret` to access it.
+///
+/// Comparing two secrets with `==` takes the same time regardless of where they differ, so only
+/// their lengths can be learned from the timing.
+pub struct SecretString(String);
+
+impl SecretString {
+    /// Creates an empty secret with pre-allocated capacity.
+    pub fn new() -> Self {
+        Self::with_capacity(INITIAL_CAPACITY)
+    }
+
+    /// Creates an empty secret with at least the given capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        SecretString(String::with_capacity(capacity))
+    }
+
+    /// Returns the password.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the length of the password in bytes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the password is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Converts into a plain `String`.
+    ///
+    /// The returned `String` is no longer wiped on drop, it is up to the caller to take care of
+    /// it.
+    pub fn into_string(mut self) -> String {
+        std::mem::take(&mut self.0)
+    }
+
+    /// Inserts a character at the given byte offset.
+    pub(crate) fn insert(&mut self, idx: usize, c: char) {
+        self.reserve(c.len_utf8());
+        self.0.insert(idx, c);
+    }
+
+    /// Removes the given byte range.
+    pub(crate) fn remove(&mut self, range: Range<usize>) {
+        self.0.replace_range(range, "");
+    }
+
+    /// Removes a trailing `\n` or `\r\n`.
+    pub(crate) fn trim_line_ending(&mut self) {
+        if self.0.ends_with('\n') {
+            self.0.pop();
+            if self.0.ends_with('\r') {
+                self.0.pop();
+            }
+        }
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        if self.0.capacity() - self.0.len() < additional {
+            let capacity = (self.0.capacity() * 2).max(self.0.len() + additional);
+            let mut grown = String::with_capacity(capacity);
+            grown.push_str(&self.0);
+            std::mem::swap(&mut self.0, &mut grown);
+            grown.zeroize();
+        }
+    }
+}
+
+impl Default for SecretString {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl PartialEq for SecretString {
+    fn eq(&self, other: &Self) -> bool {
+        let (a, b) = (self.0.as_bytes(), other.0.as_bytes());
+        if a.len() != b.len() {
+            return false;
+        }
+        let difference = a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y));
+        std::hint::black_box(difference) == 0
+    }
+}
+
+impl Eq for SecretString {}
+
+impl From<&str> for SecretString {
+    fn from(s: &str) -> Self {
+        let mut secret = Self::with_capacity(s.len().max(INITIAL_CAPACITY));
+        secret.0.push_str(s);
+        secret
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(s: String) -> Self {
+        SecretString(s)
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString([REDACTED])")
+    }
+}
+
+impl fmt::Display for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+/// Reads a line (including the line terminator) into a secret.
+///
+/// Unlike `BufRead::read_line`, intermediate buffers are wiped. When `max_length` is given,
+/// reading stops as soon as the line is known to be longer than that many characters (not
+/// counting the line terminator).
+pub(crate) fn read_line<R: BufRead>(
+    reader: &mut R,
+    max_length: Option<usize>,
+) -> io::Result<SecretString> {
+    // A character takes up to 4 bytes, and the line terminator up to 2.
+    let max_bytes = max_length.map_or(usize::MAX, |max_length| {
+        max_length.saturating_mul(4).saturating_add(2)
+    });
+    let mut bytes = Zeroizing::new(Vec::with_capacity(INITIAL_CAPACITY));
+    loop {
+        let available = match reader.fill_buf() {
+            Ok(available) => available,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if available.is_empty() {
+            break;
+        }
+        let (chunk, done) = match available.iter().position(|&b| b == b'\n') {
+            Some(i) => (&available[..=i], true),
+            None => (available, false),
+        };
+        extend(&mut bytes, chunk);
+        let read = chunk.len();
+        reader.consume(read);
+        if done {
+            break;
+        }
+        if let Some(max_length) = max_length.filter(|_| bytes.len() > max_bytes) {
+            return Err(Error::TooLong { max_length }.into());
+        }
+    }
+    let line = std::str::from_utf8(&bytes).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "stream did not contain valid UTF-8",
+        )
+    })?;
+    if let Some(max_length) = max_length {
+        if line.trim_end_matches(['\r', '\n']).chars().count() > max_length {
+            return Err(Error::TooLong { max_length }.into());
+        }
+    }
+    Ok(SecretString::from(line))
+}
+
+/// A buffered reader for secrets, whose buffer is wiped when dropped.
+pub(crate) struct SecretReader<R> {
+    inner: R,
+    buf: Zeroizing<Vec<u8>>,
+    /// The part of `buf` read but not consumed yet.
+    unread: Range<usize>,
+}
+
+impl<R: Read> SecretReader<R> {
+    /// Creates a reader with a buffer as large as the one of `BufReader`.
+    pub(crate) fn new(inner: R) -> Self {
+        Self::with_capacity(8 * 1024, inner)
+    }
+
+    /// Creates a reader with a buffer of `capacity` bytes, which never grows.
+    pub(crate) fn with_capacity(capacity: usize, inner: R) -> Self {
+        SecretReader {
+            inner,
+            buf: Zeroizing::new(vec![0; capacity]),
+            unread: 0..0,
+        }
+    }
+}
+
+impl<R: Read> Read for SecretReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let read = available.len().min(out.len());
+        out[..read].copy_from_slice(&available[..read]);
+        self.consume(read);
+        Ok(read)
+    }
+}
+
+impl<R: Read> BufRead for SecretReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.unread.is_empty() {
+            let read = self.inner.read(&mut self.buf)?;
+            self.unread = 0..read;
+        }
+        Ok(&self.buf[self.unread.clone()])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.unread.start = (self.unread.start + amount).min(self.unread.end);
+    }
+}
+
+/// Appends `chunk` to `bytes`, wiping the old allocation if it needs to grow.
+fn extend(bytes: &mut Vec<u8>, chunk: &[u8]) {
+    if bytes.capacity() - bytes.len() < chunk.len() {
+        let capacity = (bytes.capacity() * 2).max(bytes.len() + chunk.len());
+        let mut grown = Vec::with_capacity(capacity);
+        grown.extend_from_slice(bytes);
+        std::mem::swap(bytes, &mut grown);
+        grown.zeroize();
+    }
+    bytes.extend_from_slice(chunk);
+}
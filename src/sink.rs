@@ -0,0 +1,114 @@
+use crate::screen::clearing;
+use crate::Terminal;
+use std::fmt;
+use std::io::{self, stderr, stdout, Write};
+use std::sync::{Arc, Mutex};
+
+/// Where prompts, messages and echoed symbols are written to.
+///
+/// Defaults to `Stderr`, so that the output of a program can be piped or redirected without
+/// being mixed with the prompts.
+#[derive(Clone, Default)]
+pub enum PromptSink {
+    /// Standard output.
+    Stdout,
+    /// Standard error.
+    #[default]
+    Stderr,
+    /// The controlling terminal (`/dev/tty`), available on Unix-like systems only.
+    Tty,
+    /// Any writer, e.g. a buffer in tests.
+    Writer(Arc<Mutex<dyn Write + Send>>),
+}
+
+impl PromptSink {
+    /// Creates a sink writing to the given writer.
+    pub fn writer<W>(writer: W) -> Self
+    where
+        W: 'static + Write + Send,
+    {
+        PromptSink::Writer(Arc::new(Mutex::new(writer)))
+    }
+
+    pub(crate) fn open<T: Terminal>(&self, terminal: &T) -> io::Result<Output<T>> {
+        Ok(match self {
+            PromptSink::Stdout => Output::Sink(Box::new(stdout())),
+            PromptSink::Stderr => Output::Sink(Box::new(stderr())),
+            PromptSink::Tty => Output::Terminal(terminal.open_tty()?),
+            PromptSink::Writer(writer) => Output::Sink(Box::new(SharedWriter(Arc::clone(writer)))),
+        })
+    }
+}
+
+impl fmt::Debug for PromptSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PromptSink::Stdout => f.write_str("Stdout"),
+            PromptSink::Stderr => f.write_str("Stderr"),
+            PromptSink::Tty => f.write_str("Tty"),
+            PromptSink::Writer(_) => f.write_str("Writer(..)"),
+        }
+    }
+}
+
+/// An opened prompt sink, or the controlling terminal which is written to instead.
+pub(crate) enum Output<T> {
+    Sink(Box<dyn Write>),
+    Terminal(T),
+}
+
+impl<T: Terminal> Output<T> {
+    /// Clears `count` columns before the cursor, see `Terminal::clear_chars`.
+    pub(crate) fn clear_chars(&mut self, count: usize) -> io::Result<()> {
+        match self {
+            Output::Sink(sink) => {
+                sink.write_all(clearing(count).as_bytes())?;
+                sink.flush()
+            }
+            Output::Terminal(terminal) => terminal.clear_chars(count),
+        }
+    }
+}
+
+impl<T: Terminal> Write for Output<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Sink(sink) => sink.write(buf),
+            Output::Terminal(terminal) => {
+                let text = std::str::from_utf8(buf)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+                terminal.write_str(text)?;
+                Ok(buf.len())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Sink(sink) => sink.flush(),
+            Output::Terminal(_) => Ok(()),
+        }
+    }
+}
+
+struct SharedWriter(Arc<Mutex<dyn Write + Send>>);
+
+impl SharedWriter {
+    fn with_writer<T>(&self, f: impl FnOnce(&mut dyn Write) -> io::Result<T>) -> io::Result<T> {
+        let mut writer = self
+            .0
+            .lock()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "prompt sink is poisoned"))?;
+        f(&mut *writer)
+    }
+}
+
+impl Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.with_writer(|writer| writer.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.with_writer(|writer| writer.flush())
+    }
+}
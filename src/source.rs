@@ -0,0 +1,186 @@
+use crate::secret::{self, SecretReader};
+use crate::{Error, Result, SecretString};
+use std::env;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Prompt passed to an askpass program when none is given.
+const DEFAULT_ASKPASS_PROMPT: &str = "Password: ";
+
+/// Where a password can be taken from instead of asking the user, or which terminal to ask on.
+///
+/// Set an ordered list of sources with `Yapp::with_sources`. The first available one is used, and
+/// the user is asked as usual when none is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// An environment variable. Skipped when not set. Its value is used as is.
+    Env(String),
+    /// The first line of a file, without the line terminator. Skipped when the file does not
+    /// exist. A warning is printed when other users can read the file (Unix-like systems only).
+    File(PathBuf),
+    /// The first line read from an inherited file descriptor, without the line terminator.
+    /// Skipped when the descriptor is not open. Unix-like systems only.
+    Fd(i32),
+    /// An askpass program, like the ones used by `ssh` and `sudo` on desktops without a terminal.
+    ///
+    /// It is run with the prompt as its only argument, and the first line of its output is the
+    /// password. When it exits unsuccessfully (e.g. because the user closed its dialog),
+    /// `Error::Cancelled` is returned. When no program is given, the one in the `SSH_ASKPASS` or
+    /// `SUDO_ASKPASS` environment variable is used, and the source is skipped when neither is
+    /// set.
+    Askpass(Option<PathBuf>),
+    /// Ask the user on the controlling terminal, even when stdin is redirected (see
+    /// `Yapp::with_tty`). Skipped when there is no terminal.
+    Tty,
+    /// Ask the user on stdin, or read the password from it when it is redirected.
+    Stdin,
+}
+
+impl Source {
+    /// Creates a source reading the environment variable `name`.
+    pub fn env<N: Into<String>>(name: N) -> Self {
+        Source::Env(name.into())
+    }
+
+    /// Creates a source reading the file at `path`.
+    pub fn file<P: Into<PathBuf>>(path: P) -> Self {
+        Source::File(path.into())
+    }
+
+    /// Reads the password from a non-interactive source. Returns `None` when the source is not
+    /// available.
+    pub(crate) fn read(
+        &self,
+        prompt: Option<&str>,
+        max_length: Option<usize>,
+        env_var: fn(&str) -> std::result::Result<String, env::VarError>,
+        warn: impl FnOnce(&str) -> Result<()>,
+    ) -> Result<Option<SecretString>> {
+        let password = match self {
+            Source::Env(name) => match env_var(name) {
+                Ok(value) => SecretString::from(value),
+                Err(env::VarError::NotPresent) => return Ok(None),
+                Err(env::VarError::NotUnicode(_)) => {
+                    return Err(Error::Io(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("environment variable {name} is not valid UTF-8"),
+                    )))
+                }
+            },
+            Source::File(path) => {
+                let file = match File::open(path) {
+                    Ok(file) => file,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+                    Err(e) => return Err(e.into()),
+                };
+                if is_world_readable(&file)? {
+                    warn(&format!(
+                        "warning: {} is readable by other users\n",
+                        path.display()
+                    ))?;
+                }
+                read_first_line(&mut SecretReader::new(file), max_length)?
+            }
+            Source::Fd(fd) => match open_fd(*fd)? {
+                // Read byte by byte, so that nothing after the line is taken from a shared
+                // descriptor.
+                Some(file) => {
+                    read_first_line(&mut SecretReader::with_capacity(1, file), max_length)?
+                }
+                None => return Ok(None),
+            },
+            Source::Askpass(program) => {
+                let program = match program {
+                    Some(program) => program.clone(),
+                    None => match askpass_from_env() {
+                        Some(program) => program,
+                        None => return Ok(None),
+                    },
+                };
+                run_askpass(
+                    &program,
+                    prompt.unwrap_or(DEFAULT_ASKPASS_PROMPT),
+                    max_length,
+                )?
+            }
+            Source::Tty | Source::Stdin => return Ok(None),
+        };
+        match max_length {
+            Some(max_length) if password.expose_secret().chars().count() > max_length => {
+                Err(Error::TooLong { max_length })
+            }
+            _ => Ok(Some(password)),
+        }
+    }
+}
+
+fn read_first_line(
+    reader: &mut impl io::BufRead,
+    max_length: Option<usize>,
+) -> Result<SecretString> {
+    let mut line = secret::read_line(reader, max_length)?;
+    line.trim_line_ending();
+    Ok(line)
+}
+
+fn askpass_from_env() -> Option<PathBuf> {
+    ["SSH_ASKPASS", "SUDO_ASKPASS"]
+        .iter()
+        .filter_map(env::var_os)
+        .find(|program| !program.is_empty())
+        .map(PathBuf::from)
+}
+
+fn run_askpass(program: &Path, prompt: &str, max_length: Option<usize>) -> Result<SecretString> {
+    let mut child = Command::new(program)
+        .arg(prompt)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let stdout = child.stdout.take().expect("stdout of askpass is piped");
+    // The output is closed before waiting, so that the program does not block when not all of
+    // it has been read.
+    let password = read_first_line(&mut SecretReader::new(stdout), max_length);
+    if !child.wait()?.success() {
+        return Err(Error::Cancelled);
+    }
+    password
+}
+
+#[cfg(unix)]
+fn is_world_readable(file: &File) -> Result<bool> {
+    use std::os::unix::fs::PermissionsExt;
+
+    Ok(file.metadata()?.permissions().mode() & 0o004 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_world_readable(_file: &File) -> Result<bool> {
+    Ok(false)
+}
+
+/// Opens a duplicate of the descriptor, so that the original one stays open.
+#[cfg(unix)]
+fn open_fd(fd: i32) -> Result<Option<File>> {
+    use std::os::unix::io::FromRawFd;
+
+    let duplicate = unsafe { libc::dup(fd) };
+    if duplicate < 0 {
+        let e = io::Error::last_os_error();
+        return match e.raw_os_error() {
+            Some(libc::EBADF) => Ok(None),
+            _ => Err(e.into()),
+        };
+    }
+    Ok(Some(unsafe { File::from_raw_fd(duplicate) }))
+}
+
+#[cfg(not(unix))]
+fn open_fd(_fd: i32) -> Result<Option<File>> {
+    Err(Error::Io(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "file descriptors are only supported on Unix-like systems",
+    )))
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+
+/// Passwords which are among the first ones tried by anyone guessing, one per line.
+const COMMON_PASSWORDS: &str = include_str!("common_passwords.txt");
+
+/// A rough estimate of how hard a password is to guess.
+///
+/// It is based on the length of the password and the classes of characters it uses (lowercase and
+/// uppercase letters, digits and symbols). Common passwords, also when followed by digits or
+/// symbols (e.g. `password123!`), and passwords made of only a few distinct characters are always
+/// `VeryWeak`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Strength {
+    VeryWeak,
+    Weak,
+    Fair,
+    Strong,
+    VeryStrong,
+}
+
+impl Strength {
+    /// Estimates the strength of the password.
+    ///
+    /// ```rust
+    /// use yapp::Strength;
+    ///
+    /// assert_eq!(Strength::estimate("letmein1"), Strength::VeryWeak);
+    /// assert_eq!(Strength::estimate("Tr0ub4dor&3"), Strength::Strong);
+    /// ```
+    pub fn estimate(password: &str) -> Self {
+        if is_common(password) || distinct_chars(password) < 4 {
+            return Strength::VeryWeak;
+        }
+        let length_score = match password.chars().count() {
+            0..=7 => 0,
+            8..=9 => 1,
+            10..=13 => 2,
+            14..=17 => 3,
+            _ => 4,
+        };
+        match length_score + char_classes(password).saturating_sub(1) {
+            0..=1 => Strength::VeryWeak,
+            2 => Strength::Weak,
+            3..=4 => Strength::Fair,
+            5 => Strength::Strong,
+            _ => Strength::VeryStrong,
+        }
+    }
+}
+
+impl fmt::Display for Strength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Strength::VeryWeak => "very weak",
+            Strength::Weak => "weak",
+            Strength::Fair => "fair",
+            Strength::Strong => "strong",
+            Strength::VeryStrong => "very strong",
+        })
+    }
+}
+
+/// Compared character by character, so that no lowercased copy of the password is left in memory.
+fn is_common(password: &str) -> bool {
+    let stem = password.trim_end_matches(|c: char| !c.is_alphabetic());
+    let matches =
+        |common: &str, text: &str| common.chars().eq(text.chars().flat_map(char::to_lowercase));
+    COMMON_PASSWORDS
+        .lines()
+        .any(|common| matches(common, password) || (!stem.is_empty() && matches(common, stem)))
+}
+
+/// Counted without collecting the characters, so that no copy of the password is left in memory.
+fn distinct_chars(password: &str) -> usize {
+    password
+        .char_indices()
+        .filter(|&(index, c)| !password[..index].contains(c))
+        .count()
+}
+
+/// Number of character classes used: lowercase letters, uppercase letters, digits and others.
+fn char_classes(password: &str) -> usize {
+    let classes: [fn(&char) -> bool; 4] = [
+        |c| c.is_lowercase(),
+        |c| c.is_uppercase(),
+        |c| c.is_numeric(),
+        |c| !c.is_alphanumeric(),
+    ];
+    classes
+        .iter()
+        .filter(|class| password.chars().any(|c| class(&c)))
+        .count()
+}
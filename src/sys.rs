@@ -0,0 +1,229 @@
+//! Platform-specific handling of the input, used to wait for it no longer than a timeout allows.
+
+use std::io;
+use std::time::Duration;
+
+#[cfg(unix)]
+pub(crate) use unix::read_stdin;
+
+#[cfg(not(unix))]
+pub(crate) use unsupported::read_stdin;
+
+#[cfg(all(unix, feature = "console"))]
+pub(crate) use unix::KeyInput;
+
+#[cfg(all(not(unix), feature = "console"))]
+pub(crate) use unsupported::KeyInput;
+
+#[cfg(all(unix, feature = "termios"))]
+pub(crate) use unix::wait_readable;
+
+#[cfg(all(unix, feature = "pinentry-server"))]
+pub(crate) use unix::{attach_tty, detach_stdio};
+
+#[cfg(all(not(unix), feature = "pinentry-server"))]
+pub(crate) use unsupported::{attach_tty, detach_stdio};
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+    use std::os::unix::io::RawFd;
+
+    #[cfg(any(feature = "console", feature = "pinentry-server"))]
+    use std::{fs::File, io::IsTerminal, os::unix::io::AsRawFd};
+
+    #[cfg(feature = "console")]
+    use std::mem::MaybeUninit;
+
+    /// The terminal keys are read from, switched to non-canonical mode so that every key press
+    /// can be waited for. The original mode is restored when dropped.
+    #[cfg(feature = "console")]
+    pub(crate) struct KeyInput {
+        fd: RawFd,
+        original: libc::termios,
+        _tty: Option<File>,
+    }
+
+    #[cfg(feature = "console")]
+    impl KeyInput {
+        pub(crate) fn open() -> io::Result<Self> {
+            // Keys are read from stdin when it is a terminal, and from the controlling terminal
+            // otherwise, the same way `console` does.
+            let tty = if io::stdin().is_terminal() {
+                None
+            } else {
+                Some(File::open("/dev/tty")?)
+            };
+            let fd = tty.as_ref().map_or(libc::STDIN_FILENO, AsRawFd::as_raw_fd);
+            let mut termios = MaybeUninit::uninit();
+            check(unsafe { libc::tcgetattr(fd, termios.as_mut_ptr()) })?;
+            let original = unsafe { termios.assume_init() };
+            let mut termios = original;
+            termios.c_lflag &= !(libc::ICANON | libc::ECHO);
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+            check(unsafe { libc::tcsetattr(fd, libc::TCSADRAIN, &termios) })?;
+            Ok(KeyInput {
+                fd,
+                original,
+                _tty: tty,
+            })
+        }
+
+        /// Waits until a key is pressed. Returns `false` if none was pressed within the timeout.
+        pub(crate) fn wait(&self, timeout: Duration) -> io::Result<bool> {
+            wait_readable(self.fd, timeout)
+        }
+    }
+
+    #[cfg(feature = "console")]
+    impl Drop for KeyInput {
+        fn drop(&mut self) {
+            unsafe { libc::tcsetattr(self.fd, libc::TCSADRAIN, &self.original) };
+        }
+    }
+
+    /// Reads stdin into `buf`, waiting at most `timeout` for input. Returns `None` if there was
+    /// none within the timeout.
+    ///
+    /// The mode of stdin is left alone, as it is shared with the parent shell and other
+    /// processes. It is read directly rather than through `io::stdin`, whose buffer can't be
+    /// waited for, and would keep a copy of the password which is never wiped.
+    pub(crate) fn read_stdin(
+        buf: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> io::Result<Option<usize>> {
+        if let Some(timeout) = timeout {
+            if !wait_readable(libc::STDIN_FILENO, timeout)? {
+                return Ok(None);
+            }
+        }
+        loop {
+            let read =
+                unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len()) };
+            match usize::try_from(read) {
+                Ok(read) => return Ok(Some(read)),
+                Err(_) => {
+                    let e = io::Error::last_os_error();
+                    if e.kind() != io::ErrorKind::Interrupted {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Moves stdin and stdout to new descriptors, which are returned, and points them at
+    /// `/dev/null` until a terminal is attached.
+    #[cfg(feature = "pinentry-server")]
+    pub(crate) fn detach_stdio() -> io::Result<(File, File)> {
+        use std::os::unix::io::FromRawFd;
+
+        let input = unsafe { File::from_raw_fd(check(libc::dup(libc::STDIN_FILENO))?) };
+        let output = unsafe { File::from_raw_fd(check(libc::dup(libc::STDOUT_FILENO))?) };
+        let null = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/null")?;
+        redirect_stdio(&null)?;
+        Ok((input, output))
+    }
+
+    /// Points stdin and stdout at the terminal at `path`, so that keys are read from it.
+    #[cfg(feature = "pinentry-server")]
+    pub(crate) fn attach_tty(path: &str) -> io::Result<()> {
+        let tty = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+        if !tty.is_terminal() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{path} is not a terminal"),
+            ));
+        }
+        redirect_stdio(&tty)
+    }
+
+    #[cfg(feature = "pinentry-server")]
+    fn redirect_stdio(file: &File) -> io::Result<()> {
+        for fd in [libc::STDIN_FILENO, libc::STDOUT_FILENO] {
+            check(unsafe { libc::dup2(file.as_raw_fd(), fd) })?;
+        }
+        Ok(())
+    }
+
+    /// Waits until `fd` can be read. Returns `false` if it can't within the timeout.
+    pub(crate) fn wait_readable(fd: RawFd, timeout: Duration) -> io::Result<bool> {
+        let mut poll_fd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // Round up, so that waiting does not end early.
+        let millis = (timeout.as_nanos() + 999_999) / 1_000_000;
+        let millis = libc::c_int::try_from(millis).unwrap_or(libc::c_int::MAX);
+        loop {
+            match check(unsafe { libc::poll(&mut poll_fd, 1, millis) }) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => return result.map(|ready| ready > 0),
+            }
+        }
+    }
+
+    fn check(result: libc::c_int) -> io::Result<libc::c_int> {
+        if result < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod unsupported {
+    use super::*;
+
+    #[cfg(feature = "console")]
+    pub(crate) struct KeyInput;
+
+    #[cfg(feature = "console")]
+    impl KeyInput {
+        pub(crate) fn open() -> io::Result<Self> {
+            Err(unsupported())
+        }
+
+        pub(crate) fn wait(&self, _timeout: Duration) -> io::Result<bool> {
+            Err(unsupported())
+        }
+    }
+
+    pub(crate) fn read_stdin(
+        buf: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> io::Result<Option<usize>> {
+        use std::io::Read;
+
+        match timeout {
+            Some(_) => Err(unsupported()),
+            None => io::stdin().read(buf).map(Some),
+        }
+    }
+
+    #[cfg(feature = "pinentry-server")]
+    pub(crate) fn detach_stdio() -> io::Result<(std::fs::File, std::fs::File)> {
+        Err(unsupported())
+    }
+
+    #[cfg(feature = "pinentry-server")]
+    pub(crate) fn attach_tty(_path: &str) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    fn unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "timeouts are only supported on Unix-like systems",
+        )
+    }
+}
@@ -0,0 +1,113 @@
+use crate::screen::clearing;
+pub(crate) use crate::sys::read_stdin;
+use std::fs::File;
+use std::io::{self, IsTerminal, Write};
+use std::time::Duration;
+
+/// A key pressed by the user.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Key {
+    /// A character, including control characters like Ctrl-U (`'\u{15}'`).
+    Char(char),
+    Enter,
+    Escape,
+    Backspace,
+    Delete,
+    Tab,
+    Home,
+    End,
+    ArrowLeft,
+    ArrowRight,
+    ArrowUp,
+    ArrowDown,
+    CtrlC,
+    /// Any other key, which is ignored.
+    Unknown,
+}
+
+/// The `Terminal` which `Yapp::new` reads from: `ConsoleTerminal` with the `console` feature
+/// (enabled by default), and otherwise `TermiosTerminal` or `CrosstermTerminal`, whichever is
+/// enabled.
+#[cfg(feature = "console")]
+pub type DefaultTerminal = crate::ConsoleTerminal;
+
+/// The `Terminal` which `Yapp::new` reads from.
+#[cfg(all(not(feature = "console"), unix, feature = "termios"))]
+pub type DefaultTerminal = crate::TermiosTerminal;
+
+/// The `Terminal` which `Yapp::new` reads from.
+#[cfg(all(
+    not(feature = "console"),
+    not(all(unix, feature = "termios")),
+    feature = "crossterm"
+))]
+pub type DefaultTerminal = crate::CrosstermTerminal;
+
+#[cfg(not(any(
+    feature = "console",
+    all(unix, feature = "termios"),
+    feature = "crossterm"
+)))]
+compile_error!(
+    "yapp needs a terminal backend: enable the `console`, `termios` or `crossterm` feature"
+);
+
+/// A terminal backend, which `Yapp` reads passwords from.
+///
+/// `DefaultTerminal` is used unless another backend, e.g. a fake one in tests, is set with
+/// `Yapp::with_terminal`. Prompts and echoed symbols are
+/// written to the `PromptSink`, except on the controlling terminal, which is written to directly.
+///
+/// Methods taking a timeout wait at most that long for input, and return `None` if there was
+/// none. They are only called with a timeout when `Yapp::with_timeout` is set, reading can be
+/// cancelled or `Mask::ShowLast` is used, so backends which don't support it may fail with
+/// `io::ErrorKind::Unsupported`.
+pub trait Terminal: Clone {
+    /// Checks if stdin is a terminal. When it is, keys are read from it one by one. Otherwise
+    /// the password is read from redirected stdin as a line.
+    fn is_terminal(&self) -> bool;
+
+    /// Opens the controlling terminal, used instead of redirected stdin (see `Yapp::with_tty`)
+    /// and by `PromptSink::Tty`. Keys are read from and text is written to the returned terminal.
+    fn open_tty(&self) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "controlling terminal is not supported",
+        ))
+    }
+
+    /// Reads a key press without echoing it.
+    fn read_key(&self, timeout: Option<Duration>) -> io::Result<Option<Key>>;
+
+    /// Reads redirected stdin into `buf`, returning the number of bytes read (`0` at the end of
+    /// the input).
+    fn read_input(&self, buf: &mut [u8], timeout: Option<Duration>) -> io::Result<Option<usize>>;
+
+    /// Writes text at the cursor.
+    fn write_str(&self, text: &str) -> io::Result<()>;
+
+    /// Clears `count` columns before the cursor, when the last echoed symbols are deleted, and
+    /// moves the cursor back to where they started.
+    ///
+    /// By default they are overwritten with spaces, moving the cursor with backspace characters.
+    fn clear_chars(&self, count: usize) -> io::Result<()> {
+        self.write_str(&clearing(count))
+    }
+}
+
+/// Writes text to the controlling terminal if it is open, and otherwise to stdout unless it is
+/// redirected, or to stderr, like `console` does.
+#[cfg_attr(
+    not(any(feature = "crossterm", all(unix, feature = "termios"))),
+    allow(dead_code)
+)]
+pub(crate) fn write_text(tty: Option<&File>, text: &str) -> io::Result<()> {
+    let mut output: Box<dyn Write> = match tty {
+        Some(tty) => Box::new(tty),
+        None if io::stdout().is_terminal() => Box::new(io::stdout()),
+        None => Box::new(io::stderr()),
+    };
+    output.write_all(text.as_bytes())?;
+    output.flush()
+}
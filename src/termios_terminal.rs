@@ -0,0 +1,239 @@
+use crate::sys::wait_readable;
+use crate::terminal::{read_stdin, write_text};
+use crate::{Key, Terminal};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, IsTerminal};
+use std::mem::MaybeUninit;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A `Terminal` using termios directly, for programs which don't need the dependencies of
+/// `console`.
+///
+/// The terminal is switched to raw mode when the first key is read, and restored when the
+/// `TermiosTerminal` is dropped, so that nothing typed or pasted between key presses is echoed.
+/// `Yapp` reads every password with its own copy. The bytes read are decoded as UTF-8
+/// characters or the escape sequences of arrows, Home, End and Delete.
+///
+/// Available on Unix-like systems with the `termios` feature.
+pub struct TermiosTerminal {
+    /// The controlling terminal, when opened with `open_tty`.
+    tty: Option<Arc<File>>,
+    /// The terminal keys are read from, kept in raw mode until the terminal is dropped.
+    raw_mode: Mutex<Option<RawMode>>,
+}
+
+impl TermiosTerminal {
+    /// Creates a terminal on the standard streams.
+    pub const fn new() -> Self {
+        TermiosTerminal {
+            tty: None,
+            raw_mode: Mutex::new(None),
+        }
+    }
+}
+
+impl Default for TermiosTerminal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Copies share the controlling terminal, but each switches it to raw mode on its own.
+impl Clone for TermiosTerminal {
+    fn clone(&self) -> Self {
+        TermiosTerminal {
+            tty: self.tty.clone(),
+            raw_mode: Mutex::new(None),
+        }
+    }
+}
+
+impl fmt::Debug for TermiosTerminal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TermiosTerminal")
+            .field("tty", &self.tty.is_some())
+            .finish()
+    }
+}
+
+impl Terminal for TermiosTerminal {
+    fn is_terminal(&self) -> bool {
+        self.tty.is_some() || io::stdin().is_terminal()
+    }
+
+    fn open_tty(&self) -> io::Result<Self> {
+        let tty = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")?;
+        Ok(TermiosTerminal {
+            tty: Some(Arc::new(tty)),
+            raw_mode: Mutex::new(None),
+        })
+    }
+
+    fn read_key(&self, timeout: Option<Duration>) -> io::Result<Option<Key>> {
+        let mut raw_mode = self
+            .raw_mode
+            .lock()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "terminal is poisoned"))?;
+        let fd = match &*raw_mode {
+            Some(raw_mode) => raw_mode.fd,
+            None => raw_mode.insert(RawMode::enable(self.tty.clone())?).fd,
+        };
+        if let Some(timeout) = timeout {
+            if !wait_readable(fd, timeout)? {
+                return Ok(None);
+            }
+        }
+        let first = read_byte(fd)?;
+        let key = decode(first, |wait| {
+            if wait || wait_readable(fd, Duration::ZERO)? {
+                read_byte(fd).map(Some)
+            } else {
+                Ok(None)
+            }
+        })?;
+        Ok(Some(key))
+    }
+
+    fn read_input(&self, buf: &mut [u8], timeout: Option<Duration>) -> io::Result<Option<usize>> {
+        read_stdin(buf, timeout)
+    }
+
+    fn write_str(&self, text: &str) -> io::Result<()> {
+        write_text(self.tty.as_deref(), text)
+    }
+}
+
+/// The terminal keys are read from, switched to raw mode except for the output processing. The
+/// original attributes are restored when dropped.
+struct RawMode {
+    fd: RawFd,
+    original: libc::termios,
+    /// Keeps the terminal open until the attributes are restored.
+    _tty: Option<Arc<File>>,
+}
+
+impl RawMode {
+    fn enable(tty: Option<Arc<File>>) -> io::Result<Self> {
+        // Keys are read from stdin when it is a terminal, and from the controlling terminal
+        // otherwise, the same way `console` does.
+        let tty = match tty {
+            Some(tty) => Some(tty),
+            None if io::stdin().is_terminal() => None,
+            None => Some(Arc::new(File::open("/dev/tty")?)),
+        };
+        let fd = tty
+            .as_ref()
+            .map_or(libc::STDIN_FILENO, |tty| tty.as_raw_fd());
+        let mut termios = MaybeUninit::uninit();
+        check(unsafe { libc::tcgetattr(fd, termios.as_mut_ptr()) })?;
+        let original = unsafe { termios.assume_init() };
+        let mut termios = original;
+        unsafe { libc::cfmakeraw(&mut termios) };
+        // Keep translating line breaks of the output, like `console` does.
+        termios.c_oflag = original.c_oflag;
+        check(unsafe { libc::tcsetattr(fd, libc::TCSADRAIN, &termios) })?;
+        Ok(RawMode {
+            fd,
+            original,
+            _tty: tty,
+        })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe { libc::tcsetattr(self.fd, libc::TCSADRAIN, &self.original) };
+    }
+}
+
+fn read_byte(fd: RawFd) -> io::Result<u8> {
+    let mut byte = 0u8;
+    loop {
+        match unsafe { libc::read(fd, (&mut byte as *mut u8).cast(), 1) } {
+            1 => return Ok(byte),
+            0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+            _ => {
+                let e = io::Error::last_os_error();
+                if e.kind() != io::ErrorKind::Interrupted {
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+fn check(result: libc::c_int) -> io::Result<()> {
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Decodes the key press starting with the `first` byte into the key `console` reports for it.
+///
+/// `next(wait)` reads the following byte. The rest of a UTF-8 character is waited for, while an
+/// escape sequence is only read if it is already there (`next(false)` returns `None` otherwise),
+/// so that the Escape key alone is not mistaken for the start of one.
+pub(crate) fn decode<F>(first: u8, mut next: F) -> io::Result<Key>
+where
+    F: FnMut(bool) -> io::Result<Option<u8>>,
+{
+    let len = match first {
+        b'\x1b' => return decode_escape(next),
+        b'\n' | b'\r' => return Ok(Key::Enter),
+        b'\x7f' | b'\x08' => return Ok(Key::Backspace),
+        b'\t' => return Ok(Key::Tab),
+        b'\x01' => return Ok(Key::Home),
+        b'\x05' => return Ok(Key::End),
+        b'\x03' => return Ok(Key::CtrlC),
+        0x00..=0x7f => return Ok(Key::Char(char::from(first))),
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => return Ok(Key::Unknown),
+    };
+    let mut buf = [first, 0, 0, 0];
+    for byte in &mut buf[1..len] {
+        match next(true)? {
+            Some(b) => *byte = b,
+            None => return Ok(Key::Unknown),
+        }
+    }
+    Ok(std::str::from_utf8(&buf[..len])
+        .ok()
+        .and_then(|s| s.chars().next())
+        .map_or(Key::Unknown, Key::Char))
+}
+
+fn decode_escape<F>(mut next: F) -> io::Result<Key>
+where
+    F: FnMut(bool) -> io::Result<Option<u8>>,
+{
+    match next(false)? {
+        None => return Ok(Key::Escape),
+        Some(b'[' | b'O') => {}
+        Some(_) => return Ok(Key::Unknown),
+    }
+    Ok(match next(false)? {
+        Some(b'A') => Key::ArrowUp,
+        Some(b'B') => Key::ArrowDown,
+        Some(b'C') => Key::ArrowRight,
+        Some(b'D') => Key::ArrowLeft,
+        Some(b'H') => Key::Home,
+        Some(b'F') => Key::End,
+        Some(n @ b'1'..=b'8') if next(false)? == Some(b'~') => match n {
+            b'1' | b'7' => Key::Home,
+            b'3' => Key::Delete,
+            b'4' | b'8' => Key::End,
+            _ => Key::Unknown,
+        },
+        _ => Key::Unknown,
+    })
+}
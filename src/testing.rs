@@ -0,0 +1,255 @@
+//! A scripted `PasswordReader` for testing code which reads passwords.
+//!
+//! `FakeReader` answers reads with passwords, key sequences or errors queued up front, and
+//! records the prompts and what the user would have seen, so that no mocking library is needed:
+//!
+//! ```rust
+//! use yapp::testing::{FakeReader, Key};
+//! use yapp::{IsInteractive, PasswordReader};
+//!
+//! fn login<P: PasswordReader + IsInteractive>(reader: &mut P) -> yapp::Result<String> {
+//!     reader.read_password_with_prompt("Password: ")
+//! }
+//!
+//! let mut reader = FakeReader::new()
+//!     .with_echo_symbol('*')
+//!     .with_keys([Key::Char('a'), Key::Char('x'), Key::Backspace, Key::Char('b'), Key::Enter]);
+//!
+//! assert_eq!(login(&mut reader).unwrap(), "ab");
+//! reader.assert_prompts(&["Password: "]);
+//! assert_eq!(reader.output(), "Password: **\n");
+//! reader.assert_finished();
+//! ```
+//!
+//! Available with the `testing` feature, e.g. as a dev-dependency:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! yapp = { version = "*", features = ["testing"] }
+//! ```
+
+use crate::edit::{Action, LineEditor};
+use crate::{Error, IsInteractive, PasswordReader, Result, SecretString};
+use std::collections::VecDeque;
+
+pub use crate::Key;
+
+/// The answer to a single read.
+#[derive(Debug)]
+pub enum Response {
+    /// The password typed.
+    Password(String),
+    /// The keys pressed, ending with Enter, or with a key cancelling reading (Ctrl-C, Ctrl-D on
+    /// an empty line, or Escape with `FakeReader::with_cancel_on_escape`). Line editing keys
+    /// work like they do in `Yapp`.
+    Keys(Vec<Key>),
+    /// Reading fails with the error.
+    Error(Error),
+}
+
+/// A fake `PasswordReader + IsInteractive` answering reads with scripted responses.
+///
+/// Every read (including each of the two entries of a confirmation) takes the next response
+/// from the queue, and panics when there is none left.
+#[derive(Debug)]
+pub struct FakeReader {
+    responses: VecDeque<Response>,
+    interactive: bool,
+    cancel_on_escape: bool,
+    echo_symbol: Option<char>,
+    prompts: Vec<String>,
+    output: String,
+}
+
+impl Default for FakeReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeReader {
+    /// Creates an interactive reader without responses or echo symbol.
+    pub fn new() -> Self {
+        FakeReader {
+            responses: VecDeque::new(),
+            interactive: true,
+            cancel_on_escape: false,
+            echo_symbol: None,
+            prompts: Vec::new(),
+            output: String::new(),
+        }
+    }
+
+    /// Queues a response.
+    pub fn with_response(mut self, response: Response) -> Self {
+        self.responses.push_back(response);
+        self
+    }
+
+    /// Queues a typed password.
+    pub fn with_password<P: Into<String>>(self, password: P) -> Self {
+        self.with_response(Response::Password(password.into()))
+    }
+
+    /// Queues a sequence of key presses (see `Response::Keys`).
+    pub fn with_keys<K: IntoIterator<Item = Key>>(self, keys: K) -> Self {
+        self.with_response(Response::Keys(keys.into_iter().collect()))
+    }
+
+    /// Queues a failed read.
+    pub fn with_error(self, error: Error) -> Self {
+        self.with_response(Response::Error(error))
+    }
+
+    /// Sets what `is_interactive` returns. Readers are interactive by default.
+    pub fn with_interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self
+    }
+
+    /// Makes the Escape key cancel reading in key sequences.
+    pub fn with_cancel_on_escape(mut self, cancel_on_escape: bool) -> Self {
+        self.cancel_on_escape = cancel_on_escape;
+        self
+    }
+
+    /// The echo symbol set with `PasswordReader::with_echo_symbol`.
+    pub fn echo_symbol(&self) -> Option<char> {
+        self.echo_symbol
+    }
+
+    /// The prompts shown so far, including confirmation prompts.
+    pub fn prompts(&self) -> &[String] {
+        &self.prompts
+    }
+
+    /// Everything the user would have seen so far: prompts, echoed symbols, line breaks and
+    /// mismatch messages.
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    /// Number of responses not used yet.
+    pub fn remaining(&self) -> usize {
+        self.responses.len()
+    }
+
+    /// Panics unless exactly `expected` prompts have been shown, in order.
+    #[track_caller]
+    pub fn assert_prompts(&self, expected: &[&str]) {
+        assert_eq!(self.prompts, expected, "unexpected prompts");
+    }
+
+    /// Panics unless all responses have been used.
+    #[track_caller]
+    pub fn assert_finished(&self) {
+        assert!(
+            self.responses.is_empty(),
+            "{} scripted response(s) not used: {:?}",
+            self.responses.len(),
+            self.responses
+        );
+    }
+
+    fn read(&mut self, prompt: Option<&str>) -> Result<SecretString> {
+        if let Some(prompt) = prompt {
+            self.prompts.push(prompt.to_owned());
+            self.output.push_str(prompt);
+        }
+        let response = self
+            .responses
+            .pop_front()
+            .expect("FakeReader has no more scripted responses");
+        let password = match response {
+            Response::Password(password) => SecretString::from(password),
+            Response::Keys(keys) => self.type_keys(keys)?,
+            Response::Error(e) => return Err(e),
+        };
+        if let Some(symbol) = self.echo_symbol {
+            let echo = symbol
+                .to_string()
+                .repeat(password.expose_secret().chars().count());
+            self.output.push_str(&echo);
+        }
+        self.output.push('\n');
+        Ok(password)
+    }
+
+    fn type_keys(&mut self, keys: Vec<Key>) -> Result<SecretString> {
+        let mut editor = LineEditor::new(self.cancel_on_escape, None);
+        for key in keys {
+            match editor.handle_key(key) {
+                Action::Continue | Action::Rejected => {}
+                Action::Submit => return Ok(editor.into_secret()),
+                Action::Cancel => {
+                    self.output.push('\n');
+                    return Err(Error::Cancelled);
+                }
+            }
+        }
+        panic!("scripted keys should end with Enter or a key cancelling reading");
+    }
+}
+
+impl PasswordReader for FakeReader {
+    fn read_password(&mut self) -> Result<String> {
+        self.read(None).map(SecretString::into_string)
+    }
+
+    fn read_password_with_prompt(&mut self, prompt: &str) -> Result<String> {
+        self.read(Some(prompt)).map(SecretString::into_string)
+    }
+
+    fn read_secret(&mut self) -> Result<SecretString> {
+        self.read(None)
+    }
+
+    fn read_secret_with_prompt(&mut self, prompt: &str) -> Result<SecretString> {
+        self.read(Some(prompt))
+    }
+
+    fn read_password_with_confirmation(
+        &mut self,
+        prompt: &str,
+        confirm_prompt: &str,
+        mismatch_message: &str,
+        max_attempts: usize,
+    ) -> Result<String> {
+        self.read_secret_with_confirmation(prompt, confirm_prompt, mismatch_message, max_attempts)
+            .map(SecretString::into_string)
+    }
+
+    fn read_secret_with_confirmation(
+        &mut self,
+        prompt: &str,
+        confirm_prompt: &str,
+        mismatch_message: &str,
+        max_attempts: usize,
+    ) -> Result<SecretString> {
+        let attempts = max_attempts.max(1);
+        for _ in 0..attempts {
+            let password = self.read(Some(prompt))?;
+            let confirmation = self.read(Some(confirm_prompt))?;
+            if password == confirmation {
+                return Ok(password);
+            }
+            self.output.push_str(mismatch_message);
+            self.output.push('\n');
+        }
+        Err(Error::Mismatch { attempts })
+    }
+
+    fn with_echo_symbol<C>(mut self, c: C) -> Self
+    where
+        C: 'static + Into<Option<char>>,
+    {
+        self.echo_symbol = c.into();
+        self
+    }
+}
+
+impl IsInteractive for FakeReader {
+    fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+}
@@ -1,6 +1,13 @@
-use super::{IsInteractive, PasswordReader};
-use console::Key;
-use mocks::{StdOutMock, StdinMock, TermMock};
+use super::{
+    Error, IsInteractive, Key, Mask, PasswordReader, Pinentry, PromptSink, SecretString, Source,
+    Strength, Timeout, Yapp,
+};
+use mocks::{MockTerminal, StdErrMock, StdOutMock, StdinMock, TermMock};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
 #[test]
 fn when_shell_is_interactive_password_reader_intercepts_keystrokes() {
@@ -14,7 +21,7 @@ fn when_shell_is_interactive_password_reader_intercepts_keystrokes() {
         Key::Char('c'),
         Key::Enter,
     ]);
-    let mut sut = super::new();
+    let mut sut = new();
 
     let result = sut.read_password();
 
@@ -35,7 +42,7 @@ fn when_shell_is_interactive_password_reader_correctly_handles_backspace() {
         Key::Backspace,
         Key::Enter,
     ]);
-    let mut sut = super::new();
+    let mut sut = new();
 
     let result = sut.read_password();
 
@@ -43,157 +50,1602 @@ fn when_shell_is_interactive_password_reader_correctly_handles_backspace() {
     assert!(result.unwrap().is_empty());
 }
 
+#[test]
+fn password_reader_inserts_and_deletes_at_cursor() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Char('c'),
+        Key::ArrowLeft,
+        Key::Char('b'),
+        Key::Home,
+        Key::Delete,
+        Key::Char('x'),
+        Key::End,
+        Key::ArrowRight,
+        Key::Char('d'),
+        Key::Enter,
+    ]);
+    let mut sut = new();
+
+    let result = sut.read_password();
+
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap(), "xbcd");
+}
+
+#[test]
+fn password_reader_clears_line_on_ctrl_u() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Char('b'),
+        Key::Char('\u{15}'),
+        Key::Char('c'),
+        Key::Enter,
+    ]);
+    let mut sut = new();
+
+    let result = sut.read_password();
+
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap(), "c");
+}
+
+#[test]
+fn password_reader_deletes_previous_word_on_ctrl_w() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Char(' '),
+        Key::Char('b'),
+        Key::Char('c'),
+        Key::Char(' '),
+        Key::Char('\u{17}'),
+        Key::Enter,
+    ]);
+    let mut sut = new();
+
+    let result = sut.read_password();
+
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap(), "a ");
+}
+
+#[test]
+fn password_reader_is_cancelled_by_ctrl_c() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Char('a'), Key::CtrlC, Key::Enter]);
+    let mut sut = new();
+
+    let result = sut.read_password();
+
+    assert!(matches!(result, Err(Error::Cancelled)));
+}
+
+#[test]
+fn password_reader_is_cancelled_by_ctrl_d_only_when_nothing_was_typed() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Char('a'), Key::Char('\u{4}'), Key::Enter]);
+    let mut sut = new();
+
+    assert_eq!(sut.read_password().unwrap(), "a");
+
+    TermMock::setup_keys(&[Key::Char('\u{4}'), Key::Enter]);
+
+    let result = sut.read_password();
+
+    assert!(matches!(result, Err(Error::Cancelled)));
+}
+
+#[test]
+fn password_reader_is_cancelled_by_escape_only_when_enabled() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Char('a'), Key::Escape, Key::Enter]);
+    let mut sut = new();
+
+    assert_eq!(sut.read_password().unwrap(), "a");
+
+    TermMock::setup_keys(&[Key::Char('a'), Key::Escape, Key::Enter]);
+    let mut sut = new().with_cancel_on_escape(true);
+
+    let result = sut.read_password();
+
+    assert!(matches!(result, Err(Error::Cancelled)));
+}
+
+#[test]
+fn when_echo_is_disabled_backspace_does_not_change_screen() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Char('b'),
+        Key::Backspace,
+        Key::Backspace,
+        Key::Backspace,
+        Key::Enter,
+    ]);
+    let mut sut = new();
+
+    sut.read_password_with_prompt("Password: ").unwrap();
+
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert_eq!(stderr_string, "Password: \n");
+}
+
+#[test]
+fn when_echo_is_enabled_backspace_erases_one_symbol() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Char('b'),
+        Key::Backspace,
+        Key::Backspace,
+        Key::Backspace,
+        Key::Enter,
+    ]);
+    let mut sut = new().with_echo_symbol('*');
+
+    sut.read_password_with_prompt("Password: ").unwrap();
+
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert_eq!(stderr_string, "Password: **\x08 \x08\x08 \x08\n");
+    assert_eq!(visible(&stderr_bytes), "Password:\n");
+}
+
+#[test]
+fn when_echo_symbol_is_wide_backspace_erases_all_its_columns() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Char('a'), Key::Char('b'), Key::Backspace, Key::Enter]);
+    let mut sut = new().with_echo_symbol('🔒');
+
+    sut.read_password().unwrap();
+
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert_eq!(stderr_string, "🔒🔒\x08\x08  \x08\x08\n");
+}
+
+#[test]
+fn when_no_key_is_pressed_in_time_password_reader_times_out() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Char('a'), Key::Char('b')]);
+    let mut sut = new()
+        .with_echo_symbol('*')
+        .with_timeout(Timeout::Total(Duration::from_millis(10)));
+
+    let result = sut.read_password();
+
+    assert!(matches!(result, Err(Error::TimedOut)));
+    assert_eq!(visible(&StdErrMock::get_output()), "**\n");
+}
+
+#[test]
+fn idle_timeout_restarts_on_each_key_press() {
+    let delay = Duration::from_millis(40);
+    let keys = [
+        (delay, Key::Char('a')),
+        (delay, Key::Char('b')),
+        (delay, Key::Char('c')),
+        (delay, Key::Enter),
+    ];
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_delayed_keys(&keys);
+    let mut sut = new().with_timeout(Timeout::Idle(Duration::from_millis(100)));
+
+    let result = sut.read_password();
+
+    assert_eq!(result.unwrap(), "abc");
+
+    TermMock::setup_delayed_keys(&keys);
+    let mut sut = new().with_timeout(Timeout::Total(Duration::from_millis(100)));
+
+    let result = sut.read_password();
+
+    assert!(matches!(result, Err(Error::TimedOut)));
+}
+
+#[test]
+fn when_reading_is_cancelled_password_reader_stops_waiting_for_keys() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_delayed_keys(&[(Duration::from_secs(60), Key::Enter)]);
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let mut sut = new();
+    sut.cancelled = Some(Arc::clone(&cancelled));
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        cancelled.store(true, Ordering::Relaxed);
+    });
+
+    let started = Instant::now();
+    let result = sut.read_password();
+
+    assert!(matches!(result, Err(Error::Cancelled)));
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(StdErrMock::get_output(), b"\n");
+    canceller.join().unwrap();
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_password_reader_reads_password() {
+    use super::AsyncPasswordReader;
+
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Char('a'), Key::Char('b'), Key::Enter]);
+    let runtime = mocks::tokio_runtime();
+    let output = mocks::SharedOutputMock::default();
+    let mut sut = new()
+        .with_echo_symbol('*')
+        .with_prompt_sink(PromptSink::writer(output.clone()));
+
+    let result = runtime.block_on(AsyncPasswordReader::read_password_with_prompt(
+        &mut sut,
+        "Password: ",
+    ));
+
+    assert_eq!(result.unwrap(), "ab");
+    assert_eq!(visible(&output.get_output()), "Password: **\n");
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_password_reader_reads_secret_with_confirmation() {
+    use super::AsyncPasswordReader;
+
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Enter,
+        Key::Char('b'),
+        Key::Enter,
+        Key::Char('c'),
+        Key::Enter,
+        Key::Char('c'),
+        Key::Enter,
+    ]);
+    let runtime = mocks::tokio_runtime();
+    let output = mocks::SharedOutputMock::default();
+    let mut sut = new().with_prompt_sink(PromptSink::writer(output.clone()));
+
+    let result = runtime.block_on(AsyncPasswordReader::read_secret_with_confirmation(
+        &mut sut,
+        "Password: ",
+        "Repeat: ",
+        "Mismatch",
+        2,
+    ));
+
+    assert_eq!(result.unwrap().expose_secret(), "c");
+    assert_eq!(
+        visible(&output.get_output()),
+        "Password:\nRepeat:\nMismatch\nPassword:\nRepeat:\n"
+    );
+}
+
+#[test]
+fn when_timeout_is_too_long_to_be_reached_password_reader_waits_without_one() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Char('a'), Key::Enter, Key::Char('b'), Key::Enter]);
+    let mut sut = new().with_timeout(Timeout::Idle(Duration::MAX));
+
+    assert_eq!(sut.read_password().unwrap(), "a");
+
+    let mut sut = new().with_timeout(Timeout::Total(Duration::MAX));
+
+    assert_eq!(sut.read_password().unwrap(), "b");
+
+    StdinMock::set_is_terminal(false);
+    StdinMock::set_input("P455w0rd!\n");
+
+    assert_eq!(sut.read_password().unwrap(), "P455w0rd!");
+}
+
+#[test]
+fn when_stdin_stalls_non_interactive_password_reader_times_out() {
+    StdinMock::set_is_terminal(false);
+    StdinMock::set_input("P455w0rd!\n");
+    StdinMock::set_stalled(true);
+    let mut sut = new().with_timeout(Timeout::Idle(Duration::from_millis(10)));
+
+    assert_eq!(sut.read_password().unwrap(), "P455w0rd!");
+
+    let result = sut.read_password();
+
+    assert!(matches!(result, Err(Error::TimedOut)));
+}
+
+#[test]
+fn when_max_length_is_reached_password_reader_rejects_characters() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Char('b'),
+        Key::Char('c'),
+        Key::Char('d'),
+        Key::Backspace,
+        Key::Char('e'),
+        Key::Enter,
+    ]);
+    let mut sut = new()
+        .with_echo_symbol('*')
+        .with_max_length(3)
+        .with_bell(true);
+
+    let result = sut.read_password();
+
+    assert_eq!(result.unwrap(), "abe");
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert_eq!(stderr_string, "***\x07\x08 \x08*\n");
+}
+
+#[test]
+fn when_input_exceeds_max_length_non_interactive_password_reader_returns_error() {
+    StdinMock::set_is_terminal(false);
+    StdinMock::set_input("abc\r\nabcd\n");
+    let mut sut = new().with_max_length(3);
+
+    assert_eq!(sut.read_password().unwrap(), "abc");
+
+    let error = sut.read_password().unwrap_err();
+
+    assert!(matches!(error, Error::TooLong { max_length: 3 }));
+}
+
+#[test]
+fn non_interactive_password_reader_stops_reading_input_exceeding_max_length() {
+    StdinMock::set_is_terminal(false);
+    StdinMock::set_input("abcd");
+    StdinMock::set_stalled(true);
+    let mut sut = new()
+        .with_max_length(0)
+        .with_timeout(Timeout::Total(Duration::from_secs(60)));
+
+    let error = sut.read_password().unwrap_err();
+
+    assert!(matches!(error, Error::TooLong { max_length: 0 }));
+}
+
+#[test]
+fn errors_convert_to_io_errors_and_back() {
+    let error = io::Error::from(Error::Cancelled);
+
+    assert_eq!(error.kind(), io::ErrorKind::Interrupted);
+    assert!(matches!(Error::from(error), Error::Cancelled));
+
+    let error = io::Error::from(Error::Mismatch { attempts: 3 });
+
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert!(matches!(
+        Error::from(error),
+        Error::Mismatch { attempts: 3 }
+    ));
+
+    let error = Error::from(io::Error::from(io::ErrorKind::BrokenPipe));
+
+    assert!(matches!(&error, Error::Io(e) if e.kind() == io::ErrorKind::BrokenPipe));
+    assert_eq!(io::Error::from(error).kind(), io::ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn errors_wrapping_io_errors_return_them_as_source() {
+    use std::error::Error as _;
+
+    let error = Error::Io(io::Error::new(io::ErrorKind::BrokenPipe, "pipe closed"));
+
+    assert_eq!(error.source().unwrap().to_string(), "pipe closed");
+
+    let error = Error::NoTerminal(io::Error::new(io::ErrorKind::NotFound, "no tty"));
+
+    assert_eq!(error.source().unwrap().to_string(), "no tty");
+    assert!(Error::Cancelled.source().is_none());
+}
+
+#[test]
+fn when_password_is_invalid_interactive_password_reader_asks_again() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Enter,
+        Key::Char('a'),
+        Key::Char('b'),
+        Key::Enter,
+    ]);
+    let mut sut = new().with_validator(|password| {
+        if password.len() < 2 {
+            Err(String::from("Too short!"))
+        } else {
+            Ok(())
+        }
+    });
+
+    let result = sut.read_password_with_prompt("Password: ");
+
+    assert_eq!(result.unwrap(), "ab");
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert_eq!(stderr_string, "Password: \nToo short!\nPassword: \n");
+}
+
+#[test]
+fn when_validation_attempts_run_out_password_reader_returns_validation_error() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Char('a'), Key::Enter, Key::Char('b'), Key::Enter]);
+    let mut sut = new()
+        .with_validator(|_| Err(String::from("Never good enough")))
+        .with_validation_attempts(2);
+
+    let error = sut.read_password_with_prompt("Password: ").unwrap_err();
+
+    assert!(matches!(error, Error::Validation(message) if message == "Never good enough"));
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert_eq!(stderr_string, "Password: \nNever good enough\nPassword: \n");
+}
+
+#[test]
+fn when_password_is_invalid_non_interactive_password_reader_returns_validation_error() {
+    StdinMock::set_is_terminal(false);
+    StdinMock::set_input("a\nab\n");
+    let mut sut = new().with_validator(|password| {
+        if password.len() < 2 {
+            Err(String::from("Too short!"))
+        } else {
+            Ok(())
+        }
+    });
+
+    let error = sut.read_password().unwrap_err();
+
+    assert!(matches!(error, Error::Validation(_)));
+    assert!(StdErrMock::get_output().is_empty());
+}
+
+#[test]
+fn password_strength_is_estimated_from_length_and_character_classes() {
+    assert_eq!(Strength::estimate(""), Strength::VeryWeak);
+    assert_eq!(
+        Strength::estimate("aaaaaaaaaaaaaaaaaaaa"),
+        Strength::VeryWeak
+    );
+    assert_eq!(Strength::estimate("Password123!"), Strength::VeryWeak);
+    assert_eq!(Strength::estimate("kestrels"), Strength::VeryWeak);
+    assert_eq!(Strength::estimate("kestrel5"), Strength::Weak);
+    assert_eq!(Strength::estimate("Kestrel5"), Strength::Fair);
+    assert_eq!(Strength::estimate("Kestrel5-Gorse"), Strength::VeryStrong);
+}
+
+#[test]
+fn when_reveal_key_is_pressed_password_reader_toggles_plain_text() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Char('ł'),
+        Key::Tab,
+        Key::Char('c'),
+        Key::ArrowLeft,
+        Key::Tab,
+        Key::Char('d'),
+        Key::Enter,
+    ]);
+    let mut sut = new().with_echo_symbol('*').with_reveal_key(Key::Tab);
+
+    let result = sut.read_password();
+
+    assert_eq!(result.unwrap(), "ałdc");
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert_eq!(stderr_string, "**\x08\x08ałc\x08\x08\x08***\x08**\x08\n");
+    assert_eq!(visible(&stderr_bytes), "****\n");
+}
+
+#[test]
+fn when_password_is_revealed_password_reader_masks_it_on_enter_and_cancel() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Char('a'), Key::Char('\u{12}'), Key::Enter]);
+    let mut sut = new().with_reveal_key(Key::Char('\u{12}'));
+
+    let result = sut.read_password();
+
+    assert_eq!(result.unwrap(), "a");
+    let stderr_bytes = StdErrMock::get_output();
+    assert_eq!(visible(&stderr_bytes), "\n");
+
+    TermMock::setup_keys(&[Key::Char('b'), Key::Char('\u{12}'), Key::CtrlC]);
+
+    let error = sut.read_password().unwrap_err();
+
+    assert!(matches!(error, Error::Cancelled));
+    let stderr_bytes = StdErrMock::get_output();
+    assert_eq!(visible(&stderr_bytes), "\n\n");
+}
+
+#[test]
+fn fixed_and_counter_masks_hide_password_length() {
+    let keys = [
+        Key::Char('a'),
+        Key::Char('b'),
+        Key::Backspace,
+        Key::Enter,
+        Key::Char('a'),
+        Key::Backspace,
+        Key::Enter,
+    ];
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&keys);
+    let mut sut = new().with_mask(Mask::Fixed(String::from("****")));
+
+    assert_eq!(sut.read_password().unwrap(), "a");
+    assert_eq!(sut.read_password().unwrap(), "");
+
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert_eq!(
+        stderr_string,
+        "****\n****\x08\x08\x08\x08    \x08\x08\x08\x08\n"
+    );
+
+    TermMock::setup_keys(&keys);
+    let mut sut = new().with_mask(Mask::Counter);
+
+    assert_eq!(sut.read_password().unwrap(), "a");
+    assert_eq!(sut.read_password().unwrap(), "");
+
+    let stderr_bytes = StdErrMock::get_output();
+    assert_eq!(visible(&stderr_bytes), "****\n\n[1 char]\n\n");
+}
+
+#[test]
+fn decoy_mask_echoes_the_same_symbols_for_each_character_until_it_is_deleted() {
+    use crate::mask::Masking;
+
+    let mask = Mask::Decoy {
+        symbol: '*',
+        max: 3,
+    };
+    let sut = Masking::new(&mask);
+
+    let (one, one_cursor) = sut.echo("a", 1, false);
+    let (three, three_cursor) = sut.echo("abc", 3, false);
+    let (two, two_cursor) = sut.echo("abc", 2, false);
+
+    assert!((1..=3).contains(&one.len()));
+    assert!((3..=9).contains(&three.len()));
+    assert!(three.starts_with(one.as_str()));
+    assert_eq!(one_cursor, one.len());
+    assert_eq!(three_cursor, three.len());
+    assert_eq!(*two, *three);
+    assert!(one_cursor < two_cursor && two_cursor < three_cursor);
+
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Char('b'),
+        Key::Home,
+        Key::Delete,
+        Key::Delete,
+        Key::Enter,
+    ]);
+    let mut sut = new().with_mask(mask);
+
+    assert_eq!(sut.read_password().unwrap(), "");
+    assert_eq!(visible(&StdErrMock::get_output()), "\n");
+}
+
+#[test]
+fn show_last_mask_hides_last_character_on_next_key_or_after_a_while() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Char('a'), Key::Char('ł'), Key::Backspace, Key::Enter]);
+    let mut sut = new().with_mask(Mask::ShowLast('*'));
+
+    assert_eq!(sut.read_password().unwrap(), "a");
+
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert_eq!(stderr_string, "a\x08*ł\x08 \x08\n");
+
+    TermMock::setup_delayed_keys(&[
+        (Duration::ZERO, Key::Char('a')),
+        (Duration::from_millis(1100), Key::Enter),
+    ]);
+
+    assert_eq!(sut.read_password().unwrap(), "a");
+
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert!(stderr_string.ends_with("\na\x08*\n"), "{stderr_string:?}");
+}
+
+#[test]
+fn when_strength_meter_is_enabled_password_reader_shows_strength_while_typing() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Char('b'),
+        Key::Char('c'),
+        Key::Char('d'),
+        Key::Char('e'),
+        Key::Char('f'),
+        Key::Char('g'),
+        Key::Char('h'),
+        Key::Char('1'),
+        Key::Enter,
+    ]);
+    let mut sut = new().with_echo_symbol('*').with_strength_meter(true);
+
+    let result = sut.read_password();
+
+    assert_eq!(result.unwrap(), "abcdefgh1");
+    let stderr_bytes = StdErrMock::get_output();
+    assert_eq!(visible(&stderr_bytes), "*********\n");
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert!(stderr_string.starts_with("* [very weak]"));
+    assert!(stderr_string.contains("* [weak]"));
+}
+
+#[test]
+fn when_password_is_too_weak_interactive_password_reader_rejects_enter() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Char('b'),
+        Key::Enter,
+        Key::Char('C'),
+        Key::Char('4'),
+        Key::Char('@'),
+        Key::Char('z'),
+        Key::Char('x'),
+        Key::Char('y'),
+        Key::Enter,
+    ]);
+    let mut sut = new().with_min_strength(Strength::Fair).with_bell(true);
+
+    let result = sut.read_password();
+
+    assert_eq!(result.unwrap(), "abC4@zxy");
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert_eq!(stderr_string, "\x07\n");
+}
+
+#[test]
+fn when_password_is_too_weak_non_interactive_password_reader_returns_validation_error() {
+    StdinMock::set_is_terminal(false);
+    StdinMock::set_input("abc\n");
+    let mut sut = new().with_min_strength(Strength::Weak);
+
+    let error = sut.read_password().unwrap_err();
+
+    assert!(matches!(
+        error,
+        Error::Validation(message) if message == "password is very weak, it must be at least weak"
+    ));
+}
+
+#[test]
+fn password_reader_redraws_replacement_symbols_when_editing() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Char('b'),
+        Key::Char('c'),
+        Key::Home,
+        Key::Delete,
+        Key::ArrowRight,
+        Key::Char('\u{17}'),
+        Key::Enter,
+    ]);
+    let mut sut = new().with_echo_symbol('*');
+
+    let result = sut.read_password();
+
+    assert_eq!(result.unwrap(), "c");
+    assert_eq!(visible(&StdErrMock::get_output()), "*\n");
+}
+
 #[test]
 fn when_shell_is_not_interactive_password_reader_reads_from_stdin() {
     StdinMock::set_is_terminal(false);
     StdinMock::set_input("P455w0rd!");
-    let mut sut = super::new();
+    let mut sut = new();
+
+    let result = sut.read_password();
+
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap(), "P455w0rd!");
+}
+
+#[test]
+fn when_shell_is_interactive_secret_reader_intercepts_keystrokes() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Char('a'), Key::Char('b'), Key::Char('c'), Key::Enter]);
+    let mut sut = new();
+
+    let result = sut.read_secret();
+
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap().expose_secret(), "abc");
+}
+
+#[test]
+fn when_shell_is_not_interactive_password_reader_strips_line_feed() {
+    StdinMock::set_is_terminal(false);
+    StdinMock::set_input("P455w0rd!\nnext line\n");
+    let mut sut = new();
+
+    let result = sut.read_password();
+
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap(), "P455w0rd!");
+}
+
+#[test]
+fn when_shell_is_not_interactive_password_reader_strips_carriage_return_and_line_feed() {
+    StdinMock::set_is_terminal(false);
+    StdinMock::set_input("P455w0rd!\r\n");
+    let mut sut = new();
+
+    let result = sut.read_password();
+
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap(), "P455w0rd!");
+}
+
+#[test]
+fn when_shell_is_not_interactive_password_reader_reads_empty_input() {
+    StdinMock::set_is_terminal(false);
+    StdinMock::set_input("");
+    let mut sut = new();
+
+    let result = sut.read_password();
+
+    assert!(result.is_ok());
+    assert!(result.unwrap().is_empty());
+}
+
+#[test]
+fn when_line_ending_is_preserved_password_reader_returns_input_exactly() {
+    StdinMock::set_is_terminal(false);
+    StdinMock::set_input("P455w0rd!\r\nnext line\n");
+    let mut sut = new().with_preserved_line_ending(true);
+
+    let result = sut.read_password();
+
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap(), "P455w0rd!\r\n");
+}
+
+#[test]
+fn when_shell_is_not_interactive_secret_reader_reads_from_stdin() {
+    StdinMock::set_is_terminal(false);
+    StdinMock::set_input("P455w0rd!");
+    let mut sut = new();
+
+    let result = sut.read_secret();
+
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap().expose_secret(), "P455w0rd!");
+}
+
+#[test]
+fn secret_string_grows_beyond_initial_capacity() {
+    let mut sut = SecretString::with_capacity(1);
+
+    "P455w0rd!".chars().for_each(|c| sut.insert(sut.len(), c));
+
+    assert_eq!(sut.expose_secret(), "P455w0rd!");
+}
+
+#[test]
+fn secret_string_is_redacted_when_formatted() {
+    let sut = SecretString::from("P455w0rd!");
+
+    assert_eq!(format!("{sut:?}"), "SecretString([REDACTED])");
+    assert_eq!(format!("{sut}"), "[REDACTED]");
+}
+
+#[test]
+fn secret_reader_reads_no_further_than_the_line() {
+    use super::secret::{read_line, SecretReader};
+    use std::io::Read;
+
+    let mut input = io::Cursor::new("P455w0rd!\nrest");
+
+    let line = read_line(&mut SecretReader::with_capacity(1, &mut input), None).unwrap();
+
+    assert_eq!(line.expose_secret(), "P455w0rd!\n");
+    let mut rest = String::new();
+    input.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "rest");
+}
+
+#[test]
+fn secret_strings_are_equal_only_when_content_is_equal() {
+    assert_eq!(SecretString::from("abc"), SecretString::from("abc"));
+    assert_ne!(SecretString::from("abc"), SecretString::from("abd"));
+    assert_ne!(SecretString::from("abc"), SecretString::from("abcd"));
+}
+
+#[test]
+fn password_reader_returns_confirmed_password() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Enter,
+        Key::Char('b'),
+        Key::Enter,
+        Key::Char('c'),
+        Key::Enter,
+        Key::Char('c'),
+        Key::Enter,
+    ]);
+    let mut sut = new();
 
-    let result = sut.read_password();
+    let result = sut.read_password_with_confirmation("Password: ", "Confirm: ", "Mismatch!", 3);
 
     assert!(result.is_ok());
-    assert_eq!(result.unwrap(), "P455w0rd!");
+    assert_eq!(result.unwrap(), "c");
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert_eq!(
+        stderr_string,
+        "Password: \nConfirm: \nMismatch!\nPassword: \nConfirm: \n"
+    );
+}
+
+#[test]
+fn when_confirmation_attempts_run_out_password_reader_returns_confirmation_error() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Enter,
+        Key::Char('b'),
+        Key::Enter,
+        Key::Char('c'),
+        Key::Enter,
+        Key::Char('d'),
+        Key::Enter,
+    ]);
+    let mut sut = new();
+
+    let result = sut.read_secret_with_confirmation("Password: ", "Confirm: ", "Mismatch!", 2);
+
+    assert!(matches!(result, Err(Error::Mismatch { attempts: 2 })));
 }
 
 #[test]
 fn password_reader_prints_prompt() {
     StdinMock::set_is_terminal(true);
     TermMock::setup_keys(&[Key::Char('a'), Key::Char('b'), Key::Char('c'), Key::Enter]);
-    let mut sut = super::new();
+    let mut sut = new();
+
+    sut.read_password_with_prompt("Type a password: ").unwrap();
+
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert_eq!(stderr_string, "Type a password: \n");
+    assert!(StdOutMock::get_output().is_empty());
+}
+
+#[test]
+fn password_reader_prints_prompt_to_configured_sink() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Char('a'), Key::Char('b'), Key::Char('c'), Key::Enter]);
+    let mut sut = new()
+        .with_prompt_sink(PromptSink::writer(StdOutMock))
+        .with_echo_symbol('*');
 
     sut.read_password_with_prompt("Type a password: ").unwrap();
 
     let stdout_bytes = StdOutMock::get_output();
     let stdout_string = String::from_utf8_lossy(&stdout_bytes);
-    assert_eq!(stdout_string, "Type a password: ");
+    assert_eq!(stdout_string, "Type a password: ***\n");
+    assert!(StdErrMock::get_output().is_empty());
+}
+
+#[test]
+fn password_reader_prints_prompt_to_custom_writer() {
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Char('a'), Key::Char('b'), Key::Char('c'), Key::Enter]);
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let mut sut = new()
+        .with_prompt_sink(PromptSink::Writer(buffer.clone()))
+        .with_echo_symbol('*');
+
+    sut.read_password_with_prompt("Type a password: ").unwrap();
+
+    let writer_bytes = buffer.lock().unwrap();
+    let writer_string = String::from_utf8_lossy(&writer_bytes);
+    assert_eq!(writer_string, "Type a password: ***\n");
 }
 
 #[test]
 fn password_reader_prints_replacement_symbols() {
     StdinMock::set_is_terminal(true);
     TermMock::setup_keys(&[Key::Char('a'), Key::Char('b'), Key::Char('c'), Key::Enter]);
-    let mut sut = super::new().with_echo_symbol('*');
+    let mut sut = new().with_echo_symbol('*');
 
     sut.read_password().unwrap();
 
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert_eq!(stderr_string, "***\n");
+}
+
+#[test]
+fn when_stdin_is_redirected_and_tty_is_enabled_password_reader_reads_from_tty() {
+    StdinMock::set_is_terminal(false);
+    StdinMock::set_input("piped data");
+    mocks::set_tty_available(true);
+    TermMock::setup_keys(&[Key::Char('a'), Key::Char('b'), Key::Char('c'), Key::Enter]);
+    let mut sut = new().with_tty(true);
+
+    let result = sut.read_password_with_prompt("Type a password: ");
+
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap(), "abc");
+    let term_bytes = TermMock::get_output();
+    let term_string = String::from_utf8_lossy(&term_bytes);
+    assert_eq!(term_string, "Type a password: \n");
+    assert!(StdOutMock::get_output().is_empty());
+}
+
+#[test]
+fn when_symbols_are_deleted_from_end_of_line_tty_clears_them() {
+    StdinMock::set_is_terminal(false);
+    mocks::set_tty_available(true);
+    TermMock::setup_keys(&[
+        Key::Char('a'),
+        Key::Char('b'),
+        Key::Char('c'),
+        Key::Backspace,
+        Key::Backspace,
+        Key::Enter,
+    ]);
+    let mut sut = new().with_tty(true).with_echo_symbol('🔒');
+
+    let result = sut.read_password_with_prompt("P: ");
+
+    assert_eq!(result.unwrap(), "a");
+    assert_eq!(TermMock::get_cleared(), [2, 2]);
     let term_bytes = TermMock::get_output();
     let term_string = String::from_utf8_lossy(&term_bytes);
-    assert_eq!(term_string, "***\n");
+    assert_eq!(
+        term_string,
+        "P: 🔒🔒🔒\x08\x08  \x08\x08\x08\x08  \x08\x08\n"
+    );
+}
+
+#[test]
+fn when_stdin_is_redirected_and_tty_is_not_available_password_reader_returns_error() {
+    StdinMock::set_is_terminal(false);
+    StdinMock::set_input("piped data");
+    mocks::set_tty_available(false);
+    let mut sut = new().with_tty(true);
+
+    let result = sut.read_password();
+
+    assert!(matches!(result, Err(Error::NoTerminal(_))));
+}
+
+#[test]
+fn password_reader_takes_password_from_first_available_source() {
+    StdinMock::set_is_terminal(true);
+    let mut sut = new().with_sources([
+        Source::env("YAPP_TEST_SOURCE_UNSET"),
+        Source::file("/nonexistent/yapp/password"),
+        Source::env("YAPP_TEST_SOURCE_PASSWORD"),
+        Source::Stdin,
+    ]);
+    sut.env_var = |name| match name {
+        "YAPP_TEST_SOURCE_PASSWORD" => Ok(String::from("from env")),
+        _ => Err(std::env::VarError::NotPresent),
+    };
+
+    let result = sut.read_password_with_confirmation("Password: ", "Confirm: ", "Mismatch!", 3);
+
+    assert_eq!(result.unwrap(), "from env");
+    assert!(StdErrMock::get_output().is_empty());
+}
+
+#[test]
+fn when_no_source_is_available_password_reader_asks_for_password() {
+    StdinMock::set_is_terminal(false);
+    StdinMock::set_input("piped data\n");
+    mocks::set_tty_available(false);
+    let mut sut = new().with_sources([Source::env("YAPP_TEST_SOURCE_UNSET"), Source::Tty]);
+
+    let result = sut.read_password();
+
+    assert_eq!(result.unwrap(), "piped data");
+}
+
+#[cfg(unix)]
+#[test]
+fn password_reader_takes_first_line_of_file_and_warns_when_it_is_world_readable() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = std::env::temp_dir().join(format!("yapp-test-{}", std::process::id()));
+    std::fs::write(&path, "P455w0rd!\nsecond line\n").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+    let mut sut = new().with_sources([Source::File(path.clone())]);
+
+    assert_eq!(sut.read_password().unwrap(), "P455w0rd!");
+    assert!(StdErrMock::get_output().is_empty());
+
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+    let result = sut.read_password();
+
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(result.unwrap(), "P455w0rd!");
+    let stderr_bytes = StdErrMock::get_output();
+    let stderr_string = String::from_utf8_lossy(&stderr_bytes);
+    assert_eq!(
+        stderr_string,
+        format!("warning: {} is readable by other users\n", path.display())
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn password_reader_takes_only_first_line_from_file_descriptor() {
+    use std::io::{Read, Write};
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::UnixStream;
+
+    let (mut writer, mut reader) = UnixStream::pair().unwrap();
+    writer.write_all(b"P455w0rd!\nmore data").unwrap();
+    drop(writer);
+    let mut sut = new().with_sources([Source::Fd(reader.as_raw_fd())]);
+
+    let result = sut.read_password();
+
+    assert_eq!(result.unwrap(), "P455w0rd!");
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "more data");
+}
+
+#[cfg(unix)]
+#[test]
+fn password_reader_takes_password_from_askpass_program_given_the_prompt() {
+    let askpass = script_stub("askpass-prompt", "printf 'from askpass: %s\\n' \"$1\"");
+    StdinMock::set_is_terminal(false);
+    mocks::set_tty_available(false);
+    let mut sut = new().with_sources([Source::Tty, Source::Askpass(Some(askpass.clone()))]);
+
+    let result = sut.read_password_with_prompt("Unlock: ");
+
+    std::fs::remove_file(&askpass).unwrap();
+    assert_eq!(result.unwrap(), "from askpass: Unlock: ");
+    assert!(StdErrMock::get_output().is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn when_askpass_program_fails_password_reader_is_cancelled() {
+    let askpass = script_stub("askpass-cancel", "exit 1");
+    let mut sut = new().with_sources([Source::Askpass(Some(askpass.clone()))]);
+
+    let result = sut.read_password();
+
+    std::fs::remove_file(&askpass).unwrap();
+    assert!(matches!(result, Err(Error::Cancelled)));
+}
+
+#[cfg(unix)]
+#[test]
+fn pinentry_reads_percent_encoded_pin() {
+    let (program, log) = fake_pinentry("get-pin", &["P%25ss w0rd"]);
+    let mut sut = Pinentry::new()
+        .with_program(&program)
+        .with_description("Unlock\nthe key");
+
+    let result = sut.read_password_with_prompt("Passphrase: ");
+
+    assert_eq!(result.unwrap(), "P%ss w0rd");
+    assert_eq!(
+        commands_sent(&program, &log),
+        [
+            "SETDESC Unlock%0Athe key",
+            "SETPROMPT Passphrase:",
+            "GETPIN",
+            "BYE"
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn when_pinentry_is_cancelled_password_reader_returns_cancelled_error() {
+    let (program, log) = fake_pinentry("cancel", &["ERR 83886179 Operation cancelled <Pinentry>"]);
+    let mut sut = Pinentry::new().with_program(&program);
+
+    let result = sut.read_password();
+
+    commands_sent(&program, &log);
+    assert!(matches!(result, Err(Error::Cancelled)));
+}
+
+#[cfg(unix)]
+#[test]
+fn pinentry_shows_mismatch_message_as_error() {
+    let (program, log) = fake_pinentry("confirmation", &["a", "b", "c", "c"]);
+    let mut sut = Pinentry::new().with_program(&program);
+
+    let result = sut.read_password_with_confirmation("PIN:", "Repeat:", "Mismatch!", 3);
+
+    assert_eq!(result.unwrap(), "c");
+    assert_eq!(
+        commands_sent(&program, &log),
+        [
+            "SETPROMPT PIN:",
+            "GETPIN",
+            "SETPROMPT Repeat:",
+            "GETPIN",
+            "SETERROR Mismatch!",
+            "SETPROMPT PIN:",
+            "GETPIN",
+            "SETPROMPT Repeat:",
+            "GETPIN",
+            "BYE"
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn pinentry_confirms_message() {
+    let (program, log) = fake_pinentry("confirm", &["OK"]);
+    let mut sut = Pinentry::new().with_program(&program);
+
+    assert!(sut.confirm("Trust the key?").unwrap());
+
+    commands_sent(&program, &log);
+    let (program, log) = fake_pinentry("decline", &["ERR 83886194 Not confirmed <Pinentry>"]);
+    let mut sut = Pinentry::new().with_program(&program);
+
+    assert!(!sut.confirm("Trust the key?").unwrap());
+    assert_eq!(
+        commands_sent(&program, &log),
+        ["SETDESC Trust the key?", "CONFIRM", "BYE"]
+    );
+}
+
+#[cfg(feature = "pinentry-server")]
+#[test]
+fn pinentry_server_answers_get_pin_with_typed_pin() {
+    use super::PinentryServer;
+
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Char('%'), Key::Char('a'), Key::Enter, Key::Enter]);
+    let input = "OPTION ttyname=/dev/pts/7\n\
+                 SETDESC Unlock%0Athe key\n\
+                 SETPROMPT Passphrase:\n\
+                 GETPIN\n\
+                 CONFIRM\n\
+                 BYE\n";
+    let mut output = Vec::new();
+    let mut sut = PinentryServer::new(new().with_echo_symbol('*'));
+    sut.attach_tty = mocks::attach_tty;
+
+    sut.serve(input.as_bytes(), &mut output).unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "OK Pleased to meet you, yapp pinentry here\n\
+         OK\nOK\nOK\nD %25a\nOK\nOK\nOK\n"
+    );
+    assert_eq!(mocks::attached_tty().as_deref(), Some("/dev/pts/7"));
+    let stderr_bytes = StdErrMock::get_output();
+    assert_eq!(
+        visible(&stderr_bytes),
+        "Unlock\nthe key\nPassphrase: **\nUnlock\nthe key\n\
+         Press Enter to continue, or Ctrl-C to cancel\n"
+    );
+}
+
+#[cfg(feature = "pinentry-server")]
+#[test]
+fn pinentry_server_reports_cancelled_dialogs_and_unknown_commands() {
+    use super::PinentryServer;
+
+    StdinMock::set_is_terminal(true);
+    TermMock::setup_keys(&[Key::Escape, Key::Escape]);
+    let input = "GETPIN\nMESSAGE\nFOO\nGETINFO flavor\n";
+    let mut output = Vec::new();
+    let mut sut = PinentryServer::new(new().with_cancel_on_escape(true));
+    sut.attach_tty = mocks::attach_tty;
+
+    sut.serve(input.as_bytes(), &mut output).unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "OK Pleased to meet you, yapp pinentry here\n\
+         ERR 83886179 Operation cancelled <Pinentry>\n\
+         ERR 83886194 Operation cancelled <Pinentry>\n\
+         ERR 83886355 Unknown IPC command <Pinentry>\n\
+         D yapp\nOK\n"
+    );
+    assert_eq!(mocks::attached_tty().as_deref(), Some("/dev/tty"));
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn fake_reader_scripts_confirmation_mismatch() {
+    use super::testing::FakeReader;
+
+    let mut sut = FakeReader::new()
+        .with_echo_symbol('*')
+        .with_password("abc")
+        .with_password("abd")
+        .with_keys([Key::Char('x'), Key::ArrowLeft, Key::Char('w'), Key::Enter])
+        .with_password("wx");
+
+    let result = sut.read_password_with_confirmation("New: ", "Repeat: ", "Mismatch!", 3);
+
+    assert_eq!(result.unwrap(), "wx");
+    sut.assert_prompts(&["New: ", "Repeat: ", "New: ", "Repeat: "]);
+    assert_eq!(
+        sut.output(),
+        "New: ***\nRepeat: ***\nMismatch!\nNew: **\nRepeat: **\n"
+    );
+    sut.assert_finished();
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn fake_reader_returns_scripted_errors() {
+    use super::testing::FakeReader;
+
+    let mut sut = FakeReader::new()
+        .with_interactive(false)
+        .with_error(Error::TimedOut)
+        .with_cancel_on_escape(true)
+        .with_keys([Key::Char('a'), Key::Escape]);
+
+    assert!(!sut.is_interactive());
+    assert!(matches!(sut.read_password(), Err(Error::TimedOut)));
+    assert!(matches!(sut.read_secret(), Err(Error::Cancelled)));
+    assert_eq!(sut.remaining(), 0);
+}
+
+#[cfg(feature = "crossterm")]
+#[test]
+fn crossterm_keys_are_the_ones_console_reports() {
+    use crate::crossterm_terminal::from_crossterm;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    let key = |code| from_crossterm(KeyEvent::new(code, KeyModifiers::NONE));
+    let ctrl = |c| from_crossterm(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+
+    assert_eq!(key(KeyCode::Char('ł')), Key::Char('ł'));
+    assert_eq!(key(KeyCode::Backspace), Key::Backspace);
+    assert_eq!(key(KeyCode::Delete), Key::Delete);
+    assert_eq!(key(KeyCode::F(1)), Key::Unknown);
+    assert_eq!(ctrl('c'), Key::CtrlC);
+    assert_eq!(ctrl('a'), Key::Home);
+    assert_eq!(ctrl('e'), Key::End);
+    assert_eq!(ctrl('h'), Key::Backspace);
+    assert_eq!(ctrl('j'), Key::Enter);
+    assert_eq!(ctrl('d'), Key::Char('\u{4}'));
+    assert_eq!(ctrl('u'), Key::Char('\u{15}'));
+    assert_eq!(ctrl('w'), Key::Char('\u{17}'));
+}
+
+#[cfg(all(unix, feature = "termios"))]
+#[test]
+fn termios_bytes_are_decoded_into_the_keys_console_reports() {
+    use crate::termios_terminal::decode;
+
+    let key = |bytes: &[u8]| {
+        let mut rest = bytes[1..].iter().copied();
+        decode(bytes[0], |_| Ok(rest.next())).unwrap()
+    };
+
+    assert_eq!(key(b"a"), Key::Char('a'));
+    assert_eq!(key("ł".as_bytes()), Key::Char('ł'));
+    assert_eq!(key("🔒".as_bytes()), Key::Char('🔒'));
+    assert_eq!(key(&[0xf0, 0x9f]), Key::Unknown);
+    assert_eq!(key(b"\r"), Key::Enter);
+    assert_eq!(key(b"\x7f"), Key::Backspace);
+    assert_eq!(key(b"\x03"), Key::CtrlC);
+    assert_eq!(key(b"\x15"), Key::Char('\u{15}'));
+    assert_eq!(key(b"\x1b"), Key::Escape);
+    assert_eq!(key(b"\x1b[D"), Key::ArrowLeft);
+    assert_eq!(key(b"\x1bOH"), Key::Home);
+    assert_eq!(key(b"\x1b[3~"), Key::Delete);
+    assert_eq!(key(b"\x1b[5~"), Key::Unknown);
+}
+
+/// Writes a fake pinentry program, which replies to `GETPIN` and `CONFIRM` with the given
+/// replies in turn (data to send, or `OK` or `ERR` responses), and logs the commands it receives.
+#[cfg(unix)]
+fn fake_pinentry(name: &str, replies: &[&str]) -> (std::path::PathBuf, std::path::PathBuf) {
+    let base = std::env::temp_dir().join(format!("yapp-pinentry-{name}-{}", std::process::id()));
+    let replies_path = base.with_extension("replies");
+    let log_path = base.with_extension("log");
+    std::fs::write(&replies_path, replies.join("\n") + "\n").unwrap();
+    let script = format!(
+        r#"echo "OK Pleased to meet you"
+n=0
+while read -r command args; do
+  echo "$command $args" >> "{log}"
+  case "$command" in
+    GETPIN|CONFIRM)
+      n=$((n + 1))
+      reply=$(sed -n "${{n}}p" "{replies}")
+      case "$reply" in
+        OK|ERR*) echo "$reply" ;;
+        *) echo "D $reply"; echo OK ;;
+      esac ;;
+    BYE) echo OK; exit 0 ;;
+    *) echo OK ;;
+  esac
+done"#,
+        log = log_path.display(),
+        replies = replies_path.display(),
+    );
+    std::fs::remove_file(&log_path).ok();
+    (script_stub(&format!("pinentry-{name}"), &script), log_path)
+}
+
+/// Returns the commands logged by a fake pinentry, except terminal options which depend on the
+/// environment, and removes its files.
+#[cfg(unix)]
+fn commands_sent(program: &std::path::Path, log: &std::path::Path) -> Vec<String> {
+    let commands = std::fs::read_to_string(log).unwrap_or_default();
+    std::fs::remove_file(program).unwrap();
+    std::fs::remove_file(log).ok();
+    std::fs::remove_file(log.with_extension("replies")).unwrap();
+    commands
+        .lines()
+        .map(str::trim_end)
+        .filter(|command| !command.starts_with("OPTION"))
+        .map(String::from)
+        .collect()
+}
+
+/// Writes an executable shell script to the temporary directory.
+#[cfg(unix)]
+fn script_stub(name: &str, script: &str) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = std::env::temp_dir().join(format!("yapp-{name}-{}", std::process::id()));
+    std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o700)).unwrap();
+    path
+}
+
+#[test]
+fn when_tty_is_enabled_and_available_then_password_reader_is_interactive() {
+    StdinMock::set_is_terminal(false);
+    mocks::set_tty_available(true);
+
+    assert!(new().with_tty(true).is_interactive());
+    assert!(!new().is_interactive());
 }
 
 #[test]
 fn when_stdin_is_terminal_then_password_reader_is_interactive() {
     StdinMock::set_is_terminal(true);
 
-    assert!(super::new().is_interactive());
+    assert!(new().is_interactive());
 }
 
 #[test]
 fn when_stdin_is_not_terminal_then_password_reader_is_not_interactive() {
     StdinMock::set_is_terminal(false);
 
-    assert!(!super::new().is_interactive());
+    assert!(!new().is_interactive());
+}
+
+/// Creates a reader on the mocked terminal, writing prompts to the mocked stderr.
+fn new() -> Yapp<MockTerminal> {
+    Yapp::new()
+        .with_terminal(MockTerminal::default())
+        .with_prompt_sink(PromptSink::writer(StdErrMock))
+}
+
+/// Renders the output as a terminal would show it, interpreting backspace as moving the cursor
+/// back one column.
+fn visible(output: &[u8]) -> String {
+    let mut screen = String::new();
+    let mut line: Vec<char> = vec![];
+    let mut column = 0;
+    for c in String::from_utf8_lossy(output).chars() {
+        match c {
+            '\x08' => column -= 1,
+            '\n' => {
+                screen.extend(line.drain(..));
+                screen.truncate(screen.trim_end_matches(' ').len());
+                screen.push('\n');
+                column = 0;
+            }
+            c if column < line.len() => {
+                line[column] = c;
+                column += 1;
+            }
+            c => {
+                line.push(c);
+                column += 1;
+            }
+        }
+    }
+    screen.extend(line);
+    screen.trim_end_matches(' ').to_owned()
 }
 
 pub(crate) mod mocks {
-    use console::Key;
+    use crate::{Key, Terminal};
     use std::cell::RefCell;
     use std::io;
-    use std::io::Write;
+    use std::io::{Cursor, Read, Write};
+    use std::thread;
+    use std::time::Duration;
 
     thread_local! {
-        static TERM_KEYS: RefCell<Vec<Key>> = const { RefCell::new(vec![]) };
+        static TERM_KEYS: RefCell<Vec<(Duration, Key)>> = const { RefCell::new(vec![]) };
         static TERM_OUTPUT: RefCell<Vec<u8>> = const { RefCell::new(vec![]) };
+        static TERM_CLEARED: RefCell<Vec<usize>> = const { RefCell::new(vec![]) };
         static STDOUT_OUTPUT: RefCell<Vec<u8>> = const { RefCell::new(vec![]) };
+        static STDERR_OUTPUT: RefCell<Vec<u8>> = const { RefCell::new(vec![]) };
         static IS_TERMINAL: RefCell<bool> = const { RefCell::new(true) };
-        static STDIN_INPUT: RefCell<&'static str> = const { RefCell::new("") };
+        static TTY_AVAILABLE: RefCell<bool> = const { RefCell::new(false) };
+        static STDIN_INPUT: RefCell<Cursor<&'static [u8]>> = const { RefCell::new(Cursor::new(&[])) };
+        static STDIN_STALLED: RefCell<bool> = const { RefCell::new(false) };
+        static ATTACHED_TTY: RefCell<Option<String>> = const { RefCell::new(None) };
     }
 
-    pub struct TermMock;
+    /// A terminal reading the keys and input set up with `TermMock` and `StdinMock`.
+    #[derive(Debug, Clone, Default)]
+    pub struct MockTerminal {
+        /// Whether this is the controlling terminal, written to `TermMock`.
+        tty: bool,
+    }
 
-    impl TermMock {
-        pub fn setup_keys(keys: &[Key]) {
-            TERM_KEYS.with_borrow_mut(|term_keys| {
-                term_keys.clear();
-                term_keys.extend(keys.iter().rev().map(|k| k.to_owned()))
-            })
+    impl Terminal for MockTerminal {
+        fn is_terminal(&self) -> bool {
+            self.tty || IS_TERMINAL.with_borrow(|is_terminal| *is_terminal)
         }
 
-        pub fn get_output() -> Vec<u8> {
-            TERM_OUTPUT.with_borrow(Vec::clone)
+        fn open_tty(&self) -> io::Result<Self> {
+            if TTY_AVAILABLE.with_borrow(|available| *available) {
+                Ok(MockTerminal { tty: true })
+            } else {
+                Err(io::Error::from(io::ErrorKind::NotFound))
+            }
         }
 
-        pub fn stdout() -> TermMock {
-            TermMock
+        fn read_key(&self, timeout: Option<Duration>) -> io::Result<Option<Key>> {
+            if let Some(timeout) = timeout {
+                if !wait_key(timeout) {
+                    return Ok(None);
+                }
+            }
+            Ok(Some(TERM_KEYS.with_borrow_mut(|term_keys| {
+                term_keys.pop().expect("key sequence should not be empty").1
+            })))
         }
 
-        pub fn read_key(&self) -> io::Result<Key> {
-            Ok(TERM_KEYS.with_borrow_mut(|term_keys| {
-                term_keys.pop().expect("key sequence should not be empty")
-            }))
+        fn read_input(
+            &self,
+            buf: &mut [u8],
+            timeout: Option<Duration>,
+        ) -> io::Result<Option<usize>> {
+            let read = STDIN_INPUT.with_borrow_mut(|stdin| stdin.read(buf))?;
+            if read == 0 && !buf.is_empty() && STDIN_STALLED.with_borrow(|stalled| *stalled) {
+                // Stalled stdin waits for more input forever.
+                return match timeout {
+                    Some(timeout) => {
+                        thread::sleep(timeout);
+                        Ok(None)
+                    }
+                    None => Err(io::Error::from(io::ErrorKind::WouldBlock)),
+                };
+            }
+            Ok(Some(read))
         }
 
-        pub fn clear_chars(&self, n: usize) -> io::Result<()> {
-            TERM_OUTPUT.with_borrow_mut(|term_output| {
-                for _ in 0..n {
-                    term_output.pop();
-                }
-                Ok(())
+        fn write_str(&self, text: &str) -> io::Result<()> {
+            TERM_OUTPUT.with_borrow_mut(|term_output| term_output.extend(text.as_bytes()));
+            Ok(())
+        }
+
+        fn clear_chars(&self, count: usize) -> io::Result<()> {
+            TERM_CLEARED.with_borrow_mut(|term_cleared| term_cleared.push(count));
+            self.write_str(&crate::screen::clearing(count))
+        }
+    }
+
+    /// Waits for the next key as long as its delay allows.
+    fn wait_key(timeout: Duration) -> bool {
+        TERM_KEYS.with_borrow_mut(|term_keys| match term_keys.last_mut() {
+            Some((delay, _)) if *delay <= timeout => {
+                thread::sleep(*delay);
+                *delay = Duration::ZERO;
+                true
+            }
+            Some((delay, _)) => {
+                thread::sleep(timeout);
+                *delay -= timeout;
+                false
+            }
+            None => {
+                thread::sleep(timeout);
+                false
+            }
+        })
+    }
+
+    pub struct TermMock;
+
+    impl TermMock {
+        pub fn setup_keys(keys: &[Key]) {
+            TERM_KEYS.with_borrow_mut(|term_keys| {
+                term_keys.clear();
+                term_keys.extend(keys.iter().rev().map(|k| (Duration::ZERO, k.to_owned())))
             })
         }
 
-        pub fn write_line(&self, s: &str) -> io::Result<()> {
-            TERM_OUTPUT.with_borrow_mut(|term_output| {
-                write_to(term_output, s.as_bytes())?;
-                write_to(term_output, "\n".as_bytes())?;
-                Ok(())
+        /// Sets up keys, each pressed after a delay (measured from the previous key).
+        pub fn setup_delayed_keys(keys: &[(Duration, Key)]) {
+            TERM_KEYS.with_borrow_mut(|term_keys| {
+                term_keys.clear();
+                term_keys.extend(keys.iter().rev().cloned())
             })
         }
-    }
 
-    impl Write for TermMock {
-        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-            TERM_OUTPUT.with_borrow_mut(|term_output| write_to(term_output, buf))
+        pub fn get_output() -> Vec<u8> {
+            TERM_OUTPUT.with_borrow(Vec::clone)
         }
 
-        fn flush(&mut self) -> io::Result<()> {
-            Ok(())
+        /// The number of columns of every `Terminal::clear_chars` call.
+        pub fn get_cleared() -> Vec<usize> {
+            TERM_CLEARED.with_borrow(Vec::clone)
         }
     }
 
-    pub struct StdinMock;
+    pub fn set_tty_available(available: bool) {
+        TTY_AVAILABLE.with_borrow_mut(|tty_available| *tty_available = available);
+    }
+
+    /// Records the terminal instead of pointing stdin and stdout at it.
+    #[cfg(feature = "pinentry-server")]
+    pub fn attach_tty(path: &str) -> io::Result<()> {
+        ATTACHED_TTY.with_borrow_mut(|tty| *tty = Some(path.to_owned()));
+        Ok(())
+    }
 
-    pub fn stdin() -> StdinMock {
-        StdinMock
+    #[cfg(feature = "pinentry-server")]
+    pub fn attached_tty() -> Option<String> {
+        ATTACHED_TTY.with_borrow(Option::clone)
     }
 
+    pub struct StdinMock;
+
     impl StdinMock {
         pub fn set_is_terminal(is_terminal: bool) {
             IS_TERMINAL.with_borrow_mut(|terminal| *terminal = is_terminal);
         }
 
-        pub fn is_terminal(&self) -> bool {
-            IS_TERMINAL.with_borrow(|is_terminal| *is_terminal)
-        }
-
         pub fn set_input(input: &'static str) {
-            STDIN_INPUT.with_borrow_mut(|stdin| *stdin = input)
+            STDIN_INPUT.with_borrow_mut(|stdin| *stdin = Cursor::new(input.as_bytes()))
         }
 
-        pub fn read_line(&self, buf: &mut String) -> io::Result<usize> {
-            STDIN_INPUT.with_borrow_mut(|stdin| {
-                buf.push_str(stdin);
-                Ok(stdin.as_bytes().len())
-            })
+        /// Makes stdin wait for more input forever once the input set up has been read.
+        pub fn set_stalled(stalled: bool) {
+            STDIN_STALLED.with_borrow_mut(|stdin_stalled| *stdin_stalled = stalled);
         }
     }
 
+    /// Stdout, which prompts can be written to with `PromptSink::writer`.
     pub struct StdOutMock;
 
-    pub fn stdout() -> StdOutMock {
-        StdOutMock
-    }
-
     impl StdOutMock {
         pub fn get_output() -> Vec<u8> {
             STDOUT_OUTPUT.with_borrow(Vec::clone)
@@ -210,6 +1662,63 @@ pub(crate) mod mocks {
         }
     }
 
+    /// Stderr, which prompts can be written to with `PromptSink::writer`.
+    pub struct StdErrMock;
+
+    impl StdErrMock {
+        pub fn get_output() -> Vec<u8> {
+            STDERR_OUTPUT.with_borrow(Vec::clone)
+        }
+    }
+
+    impl Write for StdErrMock {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            STDERR_OUTPUT.with_borrow_mut(|stderr| write_to(stderr, buf))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Output shared between threads, which prompts can be written to with `PromptSink::writer`.
+    #[cfg(feature = "async")]
+    #[derive(Clone, Default)]
+    pub struct SharedOutputMock(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    #[cfg(feature = "async")]
+    impl SharedOutputMock {
+        pub fn get_output(&self) -> Vec<u8> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl Write for SharedOutputMock {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            write_to(&mut self.0.lock().unwrap(), buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Creates a current-thread tokio runtime, whose blocking threads start with the terminal
+    /// and keys set up on this thread.
+    #[cfg(feature = "async")]
+    pub fn tokio_runtime() -> tokio::runtime::Runtime {
+        let is_terminal = IS_TERMINAL.with_borrow(|is_terminal| *is_terminal);
+        let keys = TERM_KEYS.with_borrow(Vec::clone);
+        tokio::runtime::Builder::new_current_thread()
+            .on_thread_start(move || {
+                IS_TERMINAL.with_borrow_mut(|terminal| *terminal = is_terminal);
+                TERM_KEYS.with_borrow_mut(|term_keys| term_keys.clone_from(&keys));
+            })
+            .build()
+            .unwrap()
+    }
+
     fn write_to(target: &mut Vec<u8>, buf: &[u8]) -> io::Result<usize> {
         target.extend(buf.to_vec());
         Ok(buf.len())
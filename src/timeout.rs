@@ -0,0 +1,150 @@
+use crate::{Error, Result, Terminal};
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often waiting for input checks whether reading has been cancelled.
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long reading a password may wait for the user.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Timeout {
+    /// Maximum time to wait for the next key press (or the next chunk of redirected input).
+    /// Restarts whenever input is received.
+    Idle(Duration),
+    /// Maximum time to wait for the whole password.
+    Total(Duration),
+}
+
+/// Tracks how much longer reading may wait for input.
+struct Deadline {
+    timeout: Timeout,
+    /// `None` when the timeout is too long to ever be reached.
+    at: Option<Instant>,
+}
+
+impl Deadline {
+    fn start(timeout: Timeout) -> Self {
+        let duration = match timeout {
+            Timeout::Idle(duration) | Timeout::Total(duration) => duration,
+        };
+        Deadline {
+            timeout,
+            at: Instant::now().checked_add(duration),
+        }
+    }
+
+    fn remaining(&self) -> Duration {
+        self.at.map_or(Duration::MAX, |at| {
+            at.saturating_duration_since(Instant::now())
+        })
+    }
+
+    /// Restarts an idle timeout after input has been received.
+    fn reset(&mut self) {
+        if let Timeout::Idle(duration) = self.timeout {
+            self.at = Instant::now().checked_add(duration);
+        }
+    }
+}
+
+/// Waits for input no longer than the timeout allows, and only until reading is cancelled.
+pub(crate) struct Waiter {
+    deadline: Option<Deadline>,
+    cancelled: Option<Arc<AtomicBool>>,
+}
+
+impl Waiter {
+    /// Returns `None` when there is neither a timeout nor a way to cancel reading, so input can
+    /// simply be waited for by reading it.
+    pub(crate) fn new(
+        timeout: Option<Timeout>,
+        cancelled: Option<Arc<AtomicBool>>,
+    ) -> Option<Self> {
+        if timeout.is_none() && cancelled.is_none() {
+            return None;
+        }
+        Some(Waiter {
+            deadline: timeout.map(Deadline::start),
+            cancelled,
+        })
+    }
+
+    /// Waits for input using `wait`, which returns `false` if there was none within the given
+    /// time.
+    pub(crate) fn wait<F>(&self, mut wait: F) -> Result<()>
+    where
+        F: FnMut(Duration) -> io::Result<bool>,
+    {
+        loop {
+            if self
+                .cancelled
+                .as_ref()
+                .is_some_and(|cancelled| cancelled.load(Ordering::Relaxed))
+            {
+                return Err(Error::Cancelled);
+            }
+            let remaining = self
+                .deadline
+                .as_ref()
+                .map_or(Duration::MAX, Deadline::remaining);
+            let interval = match self.cancelled {
+                Some(_) => remaining.min(CANCEL_CHECK_INTERVAL),
+                None => remaining,
+            };
+            if wait(interval)? {
+                return Ok(());
+            }
+            if self
+                .deadline
+                .as_ref()
+                .is_some_and(|deadline| deadline.remaining().is_zero())
+            {
+                return Err(Error::TimedOut);
+            }
+        }
+    }
+
+    /// Restarts an idle timeout after input has been received.
+    pub(crate) fn reset(&mut self) {
+        if let Some(deadline) = &mut self.deadline {
+            deadline.reset();
+        }
+    }
+}
+
+/// Reads redirected stdin of a terminal, waiting for more input as long as the `Waiter` allows.
+pub(crate) struct TimedReader<'a, T> {
+    terminal: &'a T,
+    waiter: Option<Waiter>,
+}
+
+impl<'a, T: Terminal> TimedReader<'a, T> {
+    pub(crate) fn new(terminal: &'a T, waiter: Option<Waiter>) -> Self {
+        TimedReader { terminal, waiter }
+    }
+}
+
+impl<T: Terminal> Read for TimedReader<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(waiter) = &mut self.waiter else {
+            return self
+                .terminal
+                .read_input(buf, None)?
+                .ok_or_else(|| io::Error::from(io::ErrorKind::TimedOut));
+        };
+        let mut read = 0;
+        waiter.wait(
+            |timeout| match self.terminal.read_input(buf, Some(timeout))? {
+                Some(n) => {
+                    read = n;
+                    Ok(true)
+                }
+                None => Ok(false),
+            },
+        )?;
+        waiter.reset();
+        Ok(read)
+    }
+}
@@ -0,0 +1,27 @@
+use std::fmt;
+use std::sync::Arc;
+
+type Validate = dyn Fn(&str) -> Result<(), String> + Send + Sync;
+
+/// A check a password has to pass, returning a message for the user when it doesn't.
+#[derive(Clone)]
+pub(crate) struct Validator(Arc<Validate>);
+
+impl Validator {
+    pub(crate) fn new<F>(validate: F) -> Self
+    where
+        F: 'static + Fn(&str) -> Result<(), String> + Send + Sync,
+    {
+        Validator(Arc::new(validate))
+    }
+
+    pub(crate) fn validate(&self, password: &str) -> Result<(), String> {
+        (self.0)(password)
+    }
+}
+
+impl fmt::Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Validator(..)")
+    }
+}
@@ -0,0 +1,144 @@
+//! Runs the `yapp` binary the way shell scripts do.
+
+use std::io::{Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const YAPP: &str = env!("CARGO_BIN_EXE_yapp");
+
+#[test]
+fn when_stdin_is_piped_password_is_printed_to_stdout() {
+    let (status, stdout) = run(&[], "secret\n");
+
+    assert!(status.success());
+    assert_eq!(stdout, "secret\n");
+}
+
+#[test]
+fn when_confirmation_does_not_match_exit_status_is_65() {
+    let (status, stdout) = run(&["--confirm"], "a\nb\na\nc\na\nd\n");
+
+    assert_eq!(status.code(), Some(65));
+    assert_eq!(stdout, "");
+}
+
+#[test]
+fn when_password_is_too_short_exit_status_is_65() {
+    let (status, stdout) = run(&["--min-length", "8"], "short\n");
+
+    assert_eq!(status.code(), Some(65));
+    assert_eq!(stdout, "");
+}
+
+#[cfg(unix)]
+#[test]
+fn when_password_is_not_typed_in_time_exit_status_is_124() {
+    let mut child = spawn(&["--timeout", "0.2"]);
+
+    // Stdin is kept open, without any input.
+    let status = wait(&mut child);
+
+    assert_eq!(status.code(), Some(124));
+    assert_eq!(stdout(&mut child), "");
+}
+
+#[test]
+fn when_option_is_unknown_exit_status_is_2() {
+    let (status, stdout) = run(&["--bogus"], "");
+
+    assert_eq!(status.code(), Some(2));
+    assert_eq!(stdout, "");
+}
+
+#[cfg(unix)]
+#[test]
+fn when_stdout_and_stderr_are_redirected_keys_are_read_from_terminal() {
+    let (mut terminal, stdin) = open_pty();
+    let mut child = Command::new(YAPP)
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    terminal.write_all(b"abc\r").unwrap();
+    let status = wait(&mut child);
+
+    assert!(status.success());
+    assert_eq!(stdout(&mut child), "abc\n");
+}
+
+/// Runs yapp with `input` piped to stdin. Returns the exit status and the output.
+fn run(args: &[&str], input: &str) -> (ExitStatus, String) {
+    let mut child = spawn(args);
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let status = wait(&mut child);
+    (status, stdout(&mut child))
+}
+
+/// Starts yapp with stdin and stdout piped, and stderr discarded.
+fn spawn(args: &[&str]) -> Child {
+    Command::new(YAPP)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap()
+}
+
+/// Waits for the child to exit, killing it if it takes too long.
+fn wait(child: &mut Child) -> ExitStatus {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            return status;
+        }
+        if Instant::now() > deadline {
+            child.kill().unwrap();
+            panic!("yapp did not exit");
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn stdout(child: &mut Child) -> String {
+    let mut output = String::new();
+    child
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut output)
+        .unwrap();
+    output
+}
+
+/// Opens a pseudo-terminal. Returns its controlling side, and the terminal to give the child.
+#[cfg(unix)]
+fn open_pty() -> (std::fs::File, std::fs::File) {
+    use std::os::unix::io::FromRawFd;
+
+    let (mut controller, mut terminal) = (0, 0);
+    let result = unsafe {
+        libc::openpty(
+            &mut controller,
+            &mut terminal,
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            std::ptr::null(),
+        )
+    };
+    assert_eq!(result, 0, "{}", std::io::Error::last_os_error());
+    unsafe {
+        (
+            std::fs::File::from_raw_fd(controller),
+            std::fs::File::from_raw_fd(terminal),
+        )
+    }
+}